@@ -0,0 +1,317 @@
+use crate::{Session, catalog::Catalog, timestamp::Timestamp};
+use chrono::{Datelike, Days, NaiveDate, TimeDelta, Timelike, Weekday};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Gap between two consecutive sessions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gap {
+    pub length: TimeDelta,
+    pub from: Timestamp,
+    pub to: Timestamp,
+}
+
+/// Training statistics over a selection of sessions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub sessions: usize,
+    pub first: Option<Timestamp>,
+    pub last: Option<Timestamp>,
+    /// Sessions in each ISO week from the first session to the last, empty weeks included
+    pub per_week: Vec<(String, usize)>,
+    /// Sessions in each month from the first session to the last, empty months included
+    pub per_month: Vec<(String, usize)>,
+    pub average_gap: Option<TimeDelta>,
+    pub longest_gap: Option<Gap>,
+    /// Consecutive days with sessions up to today, or up to yesterday if none is logged today
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    pub busiest_weekday: Option<(Weekday, usize)>,
+    pub busiest_hour: Option<(u32, usize)>,
+    /// Volume lifted by each muscle group, largest first
+    pub volume_by_muscle: Vec<(String, f64)>,
+}
+
+/// Computes statistics of `sessions`, which are in chronological order, as of `today`.
+/// Days and hours are those of each session's own UTC offset.
+pub fn analyze(sessions: &[&Session], today: NaiveDate, catalog: &Catalog) -> Stats {
+    let days: BTreeSet<NaiveDate> = sessions.iter().map(|s| s.timestamp.date_naive()).collect();
+    let gaps: Vec<Gap> = sessions
+        .windows(2)
+        .map(|pair| Gap {
+            length: pair[1].timestamp - pair[0].timestamp,
+            from: pair[0].timestamp,
+            to: pair[1].timestamp,
+        })
+        .collect();
+    let average_gap = match (sessions.first(), sessions.last()) {
+        (Some(first), Some(last)) if !gaps.is_empty() => {
+            Some((last.timestamp - first.timestamp) / gaps.len() as i32)
+        }
+        _ => None,
+    };
+    Stats {
+        sessions: sessions.len(),
+        first: sessions.first().map(|s| s.timestamp),
+        last: sessions.last().map(|s| s.timestamp),
+        per_week: per_week(&days, sessions),
+        per_month: per_month(&days, sessions),
+        average_gap,
+        longest_gap: gaps.into_iter().max_by_key(|gap| gap.length),
+        current_streak: current_streak(&days, today),
+        longest_streak: longest_streak(&days),
+        busiest_weekday: busiest(
+            sessions
+                .iter()
+                .map(|s| s.timestamp.weekday().num_days_from_monday()),
+        )
+        .map(|(day, count)| ((0..day).fold(Weekday::Mon, |w, _| w.succ()), count)),
+        busiest_hour: busiest(sessions.iter().map(|s| s.timestamp.hour())),
+        volume_by_muscle: volume_by_muscle(sessions, catalog),
+    }
+}
+
+fn per_week(days: &BTreeSet<NaiveDate>, sessions: &[&Session]) -> Vec<(String, usize)> {
+    let (Some(first), Some(last)) = (days.first(), days.last()) else {
+        return Vec::new();
+    };
+    let mut counts: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    let monday = |date: NaiveDate| date.week(Weekday::Mon).first_day();
+    let mut week = monday(*first);
+    while week <= *last {
+        counts.insert(week, 0);
+        week = week + Days::new(7);
+    }
+    for session in sessions {
+        *counts
+            .entry(monday(session.timestamp.date_naive()))
+            .or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .map(|(monday, count)| {
+            let week = monday.iso_week();
+            (format!("{}-W{:02}", week.year(), week.week()), count)
+        })
+        .collect()
+}
+
+fn per_month(days: &BTreeSet<NaiveDate>, sessions: &[&Session]) -> Vec<(String, usize)> {
+    let (Some(first), Some(last)) = (days.first(), days.last()) else {
+        return Vec::new();
+    };
+    let mut counts: BTreeMap<(i32, u32), usize> = BTreeMap::new();
+    let (mut year, mut month) = (first.year(), first.month());
+    while (year, month) <= (last.year(), last.month()) {
+        counts.insert((year, month), 0);
+        (year, month) = if month == 12 {
+            (year + 1, 1)
+        } else {
+            (year, month + 1)
+        };
+    }
+    for session in sessions {
+        let date = session.timestamp.date_naive();
+        *counts.entry((date.year(), date.month())).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .map(|((year, month), count)| (format!("{year}-{month:02}"), count))
+        .collect()
+}
+
+fn current_streak(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> u32 {
+    let mut day = if days.contains(&today) {
+        today
+    } else {
+        today - Days::new(1)
+    };
+    let mut streak = 0;
+    while days.contains(&day) {
+        streak += 1;
+        day = day - Days::new(1);
+    }
+    streak
+}
+
+fn longest_streak(days: &BTreeSet<NaiveDate>) -> u32 {
+    let mut longest = 0;
+    let mut streak = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for day in days {
+        streak = match previous {
+            Some(previous) if previous + Days::new(1) == *day => streak + 1,
+            _ => 1,
+        };
+        longest = longest.max(streak);
+        previous = Some(*day);
+    }
+    longest
+}
+
+/// Most frequent value along with its count, preferring the earliest on ties
+fn busiest<T: Ord + Copy>(values: impl Iterator<Item = T>) -> Option<(T, usize)> {
+    let mut counts: BTreeMap<T, usize> = BTreeMap::new();
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+    counts.into_iter().rev().max_by_key(|(_, count)| *count)
+}
+
+/// Credits the volume of each exercise to the muscles its catalog entry trains. Sessions
+/// logged without exercises credit their whole volume to each of their muscles.
+fn volume_by_muscle<'a>(sessions: &[&'a Session], catalog: &'a Catalog) -> Vec<(String, f64)> {
+    let mut volumes: BTreeMap<&str, f64> = BTreeMap::new();
+    for session in sessions {
+        if session.exercises.is_empty() {
+            let Some(volume) = session.volume else {
+                continue;
+            };
+            for muscle in &session.muscles {
+                *volumes.entry(muscle).or_default() += volume;
+            }
+            continue;
+        }
+        for exercise in &session.exercises {
+            let Some(entry) = catalog.counts_as(&exercise.name) else {
+                continue;
+            };
+            let volume = exercise.volume();
+            if volume <= 0.0 {
+                continue;
+            }
+            for muscle in entry.muscles() {
+                *volumes.entry(muscle).or_default() += volume;
+            }
+        }
+    }
+    let mut volumes: Vec<(String, f64)> = volumes
+        .into_iter()
+        .map(|(muscle, volume)| (muscle.to_owned(), volume))
+        .collect();
+    volumes.sort_by(|a, b| b.1.total_cmp(&a.1));
+    volumes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exercise;
+    use chrono::DateTime;
+
+    fn session(timestamp: &str) -> Session {
+        let timestamp = DateTime::parse_from_rfc3339(timestamp).expect("test timestamp");
+        Session::new("test", timestamp)
+    }
+
+    fn date(date: &str) -> NaiveDate {
+        date.parse().expect("test date")
+    }
+
+    fn days(dates: &[&str]) -> BTreeSet<NaiveDate> {
+        dates.iter().map(|d| date(d)).collect()
+    }
+
+    #[test]
+    fn per_week_includes_empty_weeks() {
+        let sessions = [
+            session("2025-12-28T10:00:00+00:00"),
+            session("2026-01-12T10:00:00+00:00"),
+            session("2026-01-12T18:00:00+00:00"),
+        ];
+        let sessions: Vec<&Session> = sessions.iter().collect();
+        let stats = analyze(&sessions, date("2026-01-12"), &Catalog::built_in());
+        let expected = [
+            ("2025-W52", 1),
+            ("2026-W01", 0),
+            ("2026-W02", 0),
+            ("2026-W03", 2),
+        ]
+        .map(|(week, count)| (week.to_owned(), count));
+        assert_eq!(stats.per_week, expected);
+    }
+
+    #[test]
+    fn per_month_crosses_years() {
+        let sessions = [
+            session("2025-11-20T10:00:00+00:00"),
+            session("2026-01-05T10:00:00+00:00"),
+        ];
+        let sessions: Vec<&Session> = sessions.iter().collect();
+        let stats = analyze(&sessions, date("2026-01-05"), &Catalog::built_in());
+        let expected = [("2025-11", 1), ("2025-12", 0), ("2026-01", 1)]
+            .map(|(month, count)| (month.to_owned(), count));
+        assert_eq!(stats.per_month, expected);
+    }
+
+    #[test]
+    fn current_streak_counts_from_today_or_yesterday() {
+        let days = days(&["2026-10-13", "2026-10-16", "2026-10-17", "2026-10-18"]);
+        assert_eq!(current_streak(&days, date("2026-10-18")), 3);
+        assert_eq!(current_streak(&days, date("2026-10-19")), 3);
+        assert_eq!(current_streak(&days, date("2026-10-20")), 0);
+        assert_eq!(current_streak(&days, date("2026-10-14")), 1);
+    }
+
+    #[test]
+    fn longest_streak_finds_the_longest_run() {
+        let days = days(&[
+            "2026-09-29",
+            "2026-09-30",
+            "2026-10-01",
+            "2026-10-02",
+            "2026-10-05",
+            "2026-10-06",
+        ]);
+        assert_eq!(longest_streak(&days), 4);
+        assert_eq!(longest_streak(&BTreeSet::new()), 0);
+    }
+
+    #[test]
+    fn busiest_prefers_the_earliest_on_ties() {
+        assert_eq!(busiest([3, 1, 3, 1, 2].into_iter()), Some((1, 2)));
+        assert_eq!(busiest([5, 4, 5].into_iter()), Some((5, 2)));
+        assert_eq!(busiest(std::iter::empty::<u32>()), None);
+    }
+
+    #[test]
+    fn busiest_weekday_and_hour_break_ties_early() {
+        let sessions = [
+            session("2026-10-16T18:00:00+00:00"),
+            session("2026-10-12T07:00:00+00:00"),
+            session("2026-10-09T07:00:00+00:00"),
+            session("2026-10-05T18:00:00+00:00"),
+        ];
+        let sessions: Vec<&Session> = sessions.iter().collect();
+        let stats = analyze(&sessions, date("2026-10-18"), &Catalog::built_in());
+        assert_eq!(stats.busiest_weekday, Some((Weekday::Mon, 2)));
+        assert_eq!(stats.busiest_hour, Some((7, 2)));
+    }
+
+    #[test]
+    fn volume_is_credited_to_the_muscles_of_each_exercise() {
+        let mut lifting = session("2026-10-16T18:00:00+00:00");
+        lifting.volume = Some(9999.0);
+        lifting.muscles = vec!["lats".to_owned()];
+        lifting.exercises = ["bench 2x5@100kg", "squat 5@100kg", "hack squat 5@100kg"]
+            .map(|e| exercise::parse(e).expect("test exercise"))
+            .to_vec();
+        let mut plain = session("2026-10-17T18:00:00+00:00");
+        plain.volume = Some(300.0);
+        plain.muscles = vec!["chest".to_owned()];
+        let mut unmeasured = session("2026-10-18T18:00:00+00:00");
+        unmeasured.muscles = vec!["biceps".to_owned()];
+        let sessions = [&lifting, &plain, &unmeasured];
+        let stats = analyze(&sessions, date("2026-10-18"), &Catalog::built_in());
+        let expected = [
+            ("chest", 1300.0),
+            ("front-delts", 1000.0),
+            ("triceps", 1000.0),
+            ("glutes", 500.0),
+            ("hamstrings", 500.0),
+            ("lower-back", 500.0),
+            ("quads", 500.0),
+        ]
+        .map(|(muscle, volume)| (muscle.to_owned(), volume));
+        assert_eq!(stats.volume_by_muscle, expected);
+    }
+}
@@ -0,0 +1,373 @@
+use crate::{Session, Storage, migrate, persist};
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+/// Where sessions are persisted between invocations
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendKind {
+    /// A single json file, rewritten on every save
+    #[default]
+    Json,
+    /// An embedded SQLite database, updated one session at a time
+    Sqlite,
+}
+
+impl Display for BackendKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            BackendKind::Json => write!(f, "json"),
+            BackendKind::Sqlite => write!(f, "sqlite"),
+        }
+    }
+}
+
+pub trait Backend {
+    fn read(&self) -> Result<Storage>;
+    fn save(&self, storage: &Storage) -> Result<()>;
+    /// Whether nothing has been stored yet
+    fn is_empty(&self) -> Result<bool>;
+    /// Renames what is stored to a `.migrated` backup once it has moved to another backend
+    fn set_aside(&self) -> Result<()>;
+}
+
+pub fn open(kind: BackendKind, json_path: &Path, sqlite_path: &Path) -> Box<dyn Backend> {
+    match kind {
+        BackendKind::Json => Box::new(JsonFile {
+            path: json_path.to_owned(),
+        }),
+        BackendKind::Sqlite => Box::new(Sqlite {
+            path: sqlite_path.to_owned(),
+        }),
+    }
+}
+
+/// Reads versioned storage contents, upgrading them to the current version
+fn from_contents(mut contents: Value) -> Result<Storage> {
+    let upgraded = migrate::version(&contents)? < migrate::CURRENT_VERSION;
+    migrate::migrate(&mut contents)?;
+    let mut storage: Storage = serde_json::from_value(contents)
+        .context("Failed converting local file contents into sessions")?;
+    storage.sessions.sort_by_key(|s| s.timestamp);
+    // Upgraded contents are saved right away so the original is only backed up once
+    storage.dirty = upgraded;
+    Ok(storage)
+}
+
+pub struct JsonFile {
+    path: PathBuf,
+}
+
+impl Backend for JsonFile {
+    fn read(&self) -> Result<Storage> {
+        if !Path::exists(&self.path) {
+            return Ok(Storage::default());
+        }
+        let file = File::open(&self.path).context("Unable to read existing local file")?;
+        let contents: Value = serde_json::from_reader(file)
+            .context("Failed converting local file contents into json")?;
+        let version = migrate::version(&contents)?;
+        if version < migrate::CURRENT_VERSION {
+            persist::back_up_as(&self.path, &format!(".v{version}.bak"))
+                .context("Unable to back up local file before upgrading it")?;
+        }
+        from_contents(contents)
+    }
+
+    fn save(&self, storage: &Storage) -> Result<()> {
+        persist::back_up(&self.path).context("Unable to keep previous storage file when saving")?;
+        persist::write_json(&self.path, storage)
+            .context("Failed writing to existing storage file when saving")
+    }
+
+    fn is_empty(&self) -> Result<bool> {
+        Ok(self.read()?.sessions.is_empty())
+    }
+
+    fn set_aside(&self) -> Result<()> {
+        persist::set_aside(&self.path, ".migrated")
+    }
+}
+
+pub struct Sqlite {
+    path: PathBuf,
+}
+
+impl Sqlite {
+    fn connect(&self) -> Result<Connection> {
+        let connection = Connection::open(&self.path)
+            .with_context(|| format!("Unable to open database {}", self.path.display()))?;
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS sessions (
+                    identifier TEXT PRIMARY KEY,
+                    timestamp TEXT NOT NULL,
+                    data TEXT NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS meta (
+                    key TEXT PRIMARY KEY,
+                    value TEXT NOT NULL
+                );",
+            )
+            .context("Unable to create database tables")?;
+        Ok(connection)
+    }
+}
+
+impl Backend for Sqlite {
+    fn read(&self) -> Result<Storage> {
+        if !Path::exists(&self.path) {
+            return Ok(Storage::default());
+        }
+        let connection = self.connect()?;
+        let meta = |key: &str| -> Result<Option<String>> {
+            connection
+                .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+                    row.get(0)
+                })
+                .optional()
+                .with_context(|| format!("Unable to read {key} from database"))
+        };
+        let version: u32 = match meta("version")? {
+            Some(version) => version
+                .parse()
+                .context("Database has an invalid storage version")?,
+            None => migrate::CURRENT_VERSION,
+        };
+        let active: Value = match meta("active")? {
+            Some(active) => serde_json::from_str(&active).context("Invalid session in database")?,
+            None => Value::Null,
+        };
+        let mut statement = connection
+            .prepare("SELECT data FROM sessions ORDER BY timestamp")
+            .context("Unable to read sessions from database")?;
+        let sessions = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .context("Unable to read sessions from database")?
+            .map(|data| {
+                let data = data.context("Unable to read session from database")?;
+                serde_json::from_str(&data).context("Invalid session in database")
+            })
+            .collect::<Result<Vec<Value>>>()?;
+        from_contents(json!({ "version": version, "sessions": sessions, "active": active }))
+    }
+
+    fn save(&self, storage: &Storage) -> Result<()> {
+        let mut connection = self.connect()?;
+        let transaction = connection
+            .transaction()
+            .context("Unable to begin database transaction")?;
+        let stored: HashMap<String, String> = {
+            let mut statement = transaction
+                .prepare("SELECT identifier, data FROM sessions")
+                .context("Unable to read sessions from database")?;
+            statement
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .context("Unable to read sessions from database")?
+                .collect::<rusqlite::Result<_>>()
+                .context("Unable to read sessions from database")?
+        };
+        let mut current: HashMap<&str, &Session> = HashMap::new();
+        for session in &storage.sessions {
+            current.insert(&session.identifier, session);
+            let data = serde_json::to_string(session).context("Failed converting session")?;
+            if stored.get(&session.identifier) != Some(&data) {
+                transaction
+                    .execute(
+                        "INSERT OR REPLACE INTO sessions (identifier, timestamp, data)
+                         VALUES (?1, ?2, ?3)",
+                        params![
+                            session.identifier,
+                            session.timestamp.to_utc().to_rfc3339(),
+                            data
+                        ],
+                    )
+                    .context("Unable to write session to database")?;
+            }
+        }
+        for identifier in stored
+            .keys()
+            .filter(|id| !current.contains_key(id.as_str()))
+        {
+            transaction
+                .execute("DELETE FROM sessions WHERE identifier = ?1", [identifier])
+                .context("Unable to delete session from database")?;
+        }
+        transaction
+            .execute(
+                "INSERT OR REPLACE INTO meta (key, value) VALUES ('version', ?1)",
+                [storage.version.to_string()],
+            )
+            .context("Unable to write storage version to database")?;
+        match &storage.active {
+            Some(active) => transaction.execute(
+                "INSERT OR REPLACE INTO meta (key, value) VALUES ('active', ?1)",
+                [serde_json::to_string(active).context("Failed converting session")?],
+            ),
+            None => transaction.execute("DELETE FROM meta WHERE key = 'active'", []),
+        }
+        .context("Unable to write session in progress to database")?;
+        transaction
+            .commit()
+            .context("Unable to commit changes to database")
+    }
+
+    fn is_empty(&self) -> Result<bool> {
+        if !Path::exists(&self.path) {
+            return Ok(true);
+        }
+        let count: i64 = self
+            .connect()?
+            .query_row("SELECT COUNT(*) FROM sessions", [], |row| row.get(0))
+            .context("Unable to count sessions in database")?;
+        Ok(count == 0)
+    }
+
+    fn set_aside(&self) -> Result<()> {
+        persist::set_aside(&self.path, ".migrated")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, TimeDelta};
+    use std::fs;
+
+    fn storage(descriptions: &[&str]) -> Storage {
+        let timestamp = DateTime::parse_from_rfc3339("2026-10-18T09:00:00+02:00").expect("time");
+        let mut storage = Storage::default();
+        for (index, description) in descriptions.iter().enumerate() {
+            let mut session = Session::new(description, timestamp + TimeDelta::hours(index as i64));
+            session.identifier = format!("S{index}");
+            storage.sessions.push(session);
+        }
+        storage
+    }
+
+    #[test]
+    fn json_save_keeps_the_previous_contents() {
+        let directory = tempfile::tempdir().expect("temporary directory");
+        let path = directory.path().join("data.json");
+        let backend = JsonFile { path: path.clone() };
+        backend.save(&storage(&["legs"])).expect("first save");
+        let first = fs::read(&path).expect("saved file");
+        backend
+            .save(&storage(&["legs", "push"]))
+            .expect("second save");
+        assert_eq!(
+            fs::read(directory.path().join("data.json.bak")).expect("backup"),
+            first
+        );
+        let sessions = backend.read().expect("saved storage").sessions;
+        let descriptions: Vec<&str> = sessions.iter().map(|s| s.description.as_str()).collect();
+        assert_eq!(descriptions, ["legs", "push"]);
+    }
+
+    #[test]
+    fn json_read_backs_up_older_versions() {
+        for (version, fixture) in [
+            (0, include_str!("../tests/fixtures/storage-v0.json")),
+            (1, include_str!("../tests/fixtures/storage-v1.json")),
+        ] {
+            let directory = tempfile::tempdir().expect("temporary directory");
+            let path = directory.path().join("data.json");
+            fs::write(&path, fixture).expect("fixture copy");
+            let storage = JsonFile { path: path.clone() }
+                .read()
+                .expect("upgraded storage");
+            assert_eq!(storage.version, migrate::CURRENT_VERSION);
+            assert!(storage.dirty);
+            let backup = directory.path().join(format!("data.json.v{version}.bak"));
+            assert_eq!(fs::read_to_string(backup).expect("backup"), fixture);
+        }
+    }
+
+    #[test]
+    fn json_read_keeps_current_versions_as_they_are() {
+        let directory = tempfile::tempdir().expect("temporary directory");
+        let path = directory.path().join("data.json");
+        let backend = JsonFile { path: path.clone() };
+        backend.save(&storage(&["legs"])).expect("saved");
+        assert!(!backend.read().expect("current storage").dirty);
+        let names: Vec<_> = fs::read_dir(directory.path())
+            .expect("readable directory")
+            .map(|entry| entry.expect("directory entry").file_name())
+            .collect();
+        assert_eq!(names, ["data.json"]);
+    }
+
+    fn descriptions(storage: &Storage) -> Vec<&str> {
+        storage
+            .sessions
+            .iter()
+            .map(|s| s.description.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn sqlite_round_trips_changes_to_sessions() {
+        let directory = tempfile::tempdir().expect("temporary directory");
+        let backend = Sqlite {
+            path: directory.path().join("data.sqlite3"),
+        };
+        assert!(backend.is_empty().expect("missing database"));
+        let mut stored = storage(&["legs", "push", "pull"]);
+        backend.save(&stored).expect("added sessions");
+        assert!(!backend.is_empty().expect("database"));
+        assert_eq!(
+            descriptions(&backend.read().expect("read")),
+            ["legs", "push", "pull"]
+        );
+
+        stored.sessions[1].description = "push, heavy".to_owned();
+        stored.sessions.remove(2);
+        stored.active = Some(storage(&["run"]).sessions.remove(0));
+        backend.save(&stored).expect("edited sessions");
+        let read = backend.read().expect("read");
+        assert_eq!(descriptions(&read), ["legs", "push, heavy"]);
+        assert_eq!(read.active.expect("active session").description, "run");
+        assert_eq!(read.version, migrate::CURRENT_VERSION);
+        assert!(!read.dirty);
+
+        stored.active = None;
+        stored.sessions.clear();
+        backend.save(&stored).expect("removed sessions");
+        let read = backend.read().expect("read");
+        assert!(read.sessions.is_empty());
+        assert!(read.active.is_none());
+        assert!(backend.is_empty().expect("database"));
+    }
+
+    #[test]
+    fn set_aside_leaves_the_backend_empty() {
+        let directory = tempfile::tempdir().expect("temporary directory");
+        let json = JsonFile {
+            path: directory.path().join("data.json"),
+        };
+        let sqlite = Sqlite {
+            path: directory.path().join("data.sqlite3"),
+        };
+        for backend in [&json as &dyn Backend, &sqlite] {
+            backend.save(&storage(&["legs"])).expect("saved");
+            backend.set_aside().expect("set aside");
+            assert!(backend.is_empty().expect("emptied backend"));
+        }
+        let migrated = Sqlite {
+            path: directory.path().join("data.sqlite3.migrated"),
+        };
+        assert_eq!(descriptions(&migrated.read().expect("read")), ["legs"]);
+        let migrated = JsonFile {
+            path: directory.path().join("data.json.migrated"),
+        };
+        assert_eq!(descriptions(&migrated.read().expect("read")), ["legs"]);
+    }
+}
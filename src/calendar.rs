@@ -0,0 +1,187 @@
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use std::{collections::BTreeMap, fmt::Write};
+
+const WEEKDAYS: [&str; 7] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const RESET: &str = "\x1b[0m";
+const UNDERLINE: &str = "\x1b[4m";
+
+/// Number of workout sessions on each day
+pub type Counts = BTreeMap<NaiveDate, usize>;
+
+/// Background of a day in the terminal, greener the more sessions it had
+fn shade(count: usize) -> &'static str {
+    match count {
+        0 => "",
+        1 => "\x1b[30;48;5;114m",
+        2 => "\x1b[30;48;5;71m",
+        _ => "\x1b[97;48;5;28m",
+    }
+}
+
+/// Colour of a day's square in the year heatmap
+fn tint(count: usize) -> &'static str {
+    match count {
+        0 => "\x1b[38;5;238m",
+        1 => "\x1b[38;5;114m",
+        2 => "\x1b[38;5;71m",
+        _ => "\x1b[38;5;28m",
+    }
+}
+
+/// Count as a single character, for output without colour
+fn mark(count: usize) -> char {
+    match count {
+        0 => '.',
+        1..=9 => char::from_digit(count as u32, 10).expect("count should be a single digit"),
+        _ => '+',
+    }
+}
+
+/// Grid of the month starting on `first`, from Monday to Sunday. Days with sessions are
+/// marked with their count, or shaded by it when `colour` is set, and today is underlined.
+pub fn month(first: NaiveDate, counts: &Counts, today: NaiveDate, colour: bool) -> String {
+    let next = first + Months::new(1);
+    let mut out = String::new();
+    let title = first.format("%B %Y").to_string();
+    let width = WEEKDAYS.len() * 5 - 1;
+    let _ = writeln!(out, "{}", format!("{title:^width$}").trim_end());
+    let _ = writeln!(
+        out,
+        "{}",
+        WEEKDAYS.map(|day| format!("{day:>3}")).join("  ")
+    );
+    let mut line = "     ".repeat(first.weekday().num_days_from_monday() as usize);
+    let mut day = first;
+    while day < next {
+        let count = counts.get(&day).copied().unwrap_or_default();
+        if colour {
+            let underline = if day == today { UNDERLINE } else { "" };
+            let style = format!("{}{underline}", shade(count));
+            let reset = if style.is_empty() { "" } else { RESET };
+            let _ = write!(line, " {style}{:>2}{reset}  ", day.day());
+        } else {
+            let marker = match count {
+                0 => String::new(),
+                1 => "*".to_owned(),
+                _ => format!("*{}", mark(count)),
+            };
+            let _ = write!(line, " {:>2}{marker:<2}", day.day());
+        }
+        if day.weekday() == Weekday::Sun {
+            let _ = writeln!(out, "{}", line.trim_end());
+            line.clear();
+        }
+        day = day + Days::new(1);
+    }
+    if !line.is_empty() {
+        let _ = writeln!(out, "{}", line.trim_end());
+    }
+    if !colour {
+        let _ = writeln!(out, "\n* one session, *N N sessions");
+    }
+    out
+}
+
+/// Year heatmap with a column per week and a row per weekday, like contribution graphs.
+/// Days are shaded by their sessions when `colour` is set, otherwise shown by count, and
+/// days still to come are left blank.
+pub fn year(year: i32, counts: &Counts, today: NaiveDate, colour: bool) -> String {
+    let Some(first) = NaiveDate::from_ymd_opt(year, 1, 1) else {
+        return String::new();
+    };
+    let start = first.week(Weekday::Mon).first_day();
+    let last = NaiveDate::from_ymd_opt(year, 12, 31).unwrap_or(first);
+    let weeks = (last - start).num_days() as usize / 7 + 1;
+    let mut out = String::new();
+    let mut labels = vec![' '; weeks * 2 + 1];
+    for (index, name) in MONTHS.iter().enumerate() {
+        let Some(month) = NaiveDate::from_ymd_opt(year, index as u32 + 1, 1) else {
+            continue;
+        };
+        let column = (month - start).num_days() as usize / 7 * 2;
+        for (offset, c) in name.chars().enumerate() {
+            if let Some(label) = labels.get_mut(column + offset) {
+                *label = c;
+            }
+        }
+    }
+    let _ = writeln!(out, "   {}", labels.iter().collect::<String>().trim_end());
+    for (row, weekday) in WEEKDAYS.iter().enumerate() {
+        let mut line = format!("{weekday} ");
+        for week in 0..weeks {
+            let day = start + Days::new((week * 7 + row) as u64);
+            let count = counts.get(&day).copied().unwrap_or_default();
+            if day.year() != year || day > today {
+                line.push_str("  ");
+            } else if colour {
+                let underline = if day == today { UNDERLINE } else { "" };
+                let _ = write!(line, "{}{underline}■{RESET} ", tint(count));
+            } else {
+                let _ = write!(line, "{} ", mark(count));
+            }
+        }
+        let _ = writeln!(out, "{}", line.trim_end());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("valid date")
+    }
+
+    #[test]
+    fn month_marks_sessions_from_the_weekday_of_the_first() {
+        let counts = Counts::from([
+            (date(2026, 10, 1), 1),
+            (date(2026, 10, 5), 3),
+            (date(2026, 10, 11), 12),
+        ]);
+        let expected = "           October 2026
+ Mo   Tu   We   Th   Fr   Sa   Su
+                 1*   2    3    4
+  5*3  6    7    8    9   10   11*+
+ 12   13   14   15   16   17   18
+ 19   20   21   22   23   24   25
+ 26   27   28   29   30   31
+
+* one session, *N N sessions
+";
+        assert_eq!(
+            month(date(2026, 10, 1), &counts, date(2026, 10, 18), false),
+            expected
+        );
+    }
+
+    #[test]
+    fn year_leaves_days_to_come_and_of_the_previous_year_blank() {
+        let counts = Counts::from([(date(2026, 1, 1), 2), (date(2026, 1, 5), 10)]);
+        let expected = "   Jan     Feb     Mar       Apr     May       Jun     Jul     Aug       Sep     Oct     Nov       Dec
+Mo   + .
+Tu   . .
+We   . .
+Th 2 .
+Fr . .
+Sa . .
+Su . .
+";
+        assert_eq!(year(2026, &counts, date(2026, 1, 14), false), expected);
+    }
+
+    #[test]
+    fn year_leaves_days_of_the_next_year_blank() {
+        let counts = Counts::from([(date(2026, 12, 31), 1), (date(2027, 1, 1), 1)]);
+        let calendar = year(2026, &counts, date(2027, 6, 1), false);
+        let rows: Vec<&str> = calendar.lines().skip(1).collect();
+        // The last week runs from Monday 28 December 2026 to Sunday 3 January 2027
+        assert!(rows[3].ends_with(". 1"), "{}", rows[3]);
+        assert_eq!(rows[4].len(), rows[3].len() - 2);
+        assert!(rows[4].ends_with(" ."), "{}", rows[4]);
+    }
+}
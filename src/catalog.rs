@@ -0,0 +1,490 @@
+use crate::{exercise, exercise::Exercise, persist};
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+/// Name, primary muscles, secondary muscles and aliases of a built-in exercise
+type BuiltIn = (
+    &'static str,
+    &'static [&'static str],
+    &'static [&'static str],
+    &'static [&'static str],
+);
+
+const BUILT_IN: &[BuiltIn] = &[
+    (
+        "bench press",
+        &["chest"],
+        &["triceps", "front-delts"],
+        &["bench", "flat bench"],
+    ),
+    (
+        "incline bench press",
+        &["chest", "front-delts"],
+        &["triceps"],
+        &["incline bench"],
+    ),
+    (
+        "dumbbell press",
+        &["chest"],
+        &["triceps", "front-delts"],
+        &["db press"],
+    ),
+    ("chest fly", &["chest"], &["front-delts"], &["fly", "flye"]),
+    (
+        "push-up",
+        &["chest"],
+        &["triceps", "front-delts"],
+        &["pushup", "push up"],
+    ),
+    ("dips", &["chest", "triceps"], &["front-delts"], &["dip"]),
+    (
+        "overhead press",
+        &["front-delts"],
+        &["triceps", "side-delts"],
+        &["ohp", "military press"],
+    ),
+    ("lateral raise", &["side-delts"], &[], &["side raise"]),
+    ("face pull", &["rear-delts"], &["upper-back"], &[]),
+    (
+        "triceps extension",
+        &["triceps"],
+        &[],
+        &["skull crusher", "pushdown"],
+    ),
+    (
+        "biceps curl",
+        &["biceps"],
+        &["forearms"],
+        &["curl", "bicep curl"],
+    ),
+    ("hammer curl", &["biceps", "forearms"], &[], &[]),
+    (
+        "pull-up",
+        &["lats"],
+        &["biceps", "upper-back"],
+        &["pullup", "pull up"],
+    ),
+    (
+        "chin-up",
+        &["lats", "biceps"],
+        &["upper-back"],
+        &["chinup", "chin up"],
+    ),
+    ("lat pulldown", &["lats"], &["biceps"], &["pulldown"]),
+    (
+        "barbell row",
+        &["upper-back", "lats"],
+        &["biceps", "rear-delts"],
+        &["row", "bent over row"],
+    ),
+    (
+        "seated cable row",
+        &["upper-back"],
+        &["lats", "biceps"],
+        &["cable row"],
+    ),
+    ("shrug", &["traps"], &["forearms"], &["shrugs"]),
+    (
+        "deadlift",
+        &["hamstrings", "glutes", "lower-back"],
+        &["traps", "forearms"],
+        &["dl"],
+    ),
+    (
+        "romanian deadlift",
+        &["hamstrings", "glutes"],
+        &["lower-back"],
+        &["rdl"],
+    ),
+    (
+        "squat",
+        &["quads", "glutes"],
+        &["hamstrings", "lower-back"],
+        &["back squat", "squats"],
+    ),
+    ("front squat", &["quads"], &["glutes", "upper-back"], &[]),
+    ("leg press", &["quads"], &["glutes"], &[]),
+    ("lunge", &["quads", "glutes"], &["hamstrings"], &["lunges"]),
+    ("leg extension", &["quads"], &[], &[]),
+    ("leg curl", &["hamstrings"], &[], &["hamstring curl"]),
+    (
+        "hip thrust",
+        &["glutes"],
+        &["hamstrings"],
+        &["glute bridge"],
+    ),
+    ("calf raise", &["calves"], &[], &["calf raises"]),
+    ("plank", &["abs"], &["obliques"], &[]),
+    ("crunch", &["abs"], &[], &["crunches", "sit-up"]),
+];
+
+/// An exercise and the muscle groups it trains
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Entry {
+    pub name: String,
+    pub primary: Vec<String>,
+    #[serde(default)]
+    pub secondary: Vec<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Whether the entry comes from the catalog file rather than the built-in list
+    #[serde(skip)]
+    pub custom: bool,
+}
+
+impl Entry {
+    pub fn muscles(&self) -> impl Iterator<Item = &String> {
+        self.primary.iter().chain(&self.secondary)
+    }
+}
+
+/// Exercises and aliases added by the user, extending and overriding the built-in ones
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct CatalogFile {
+    exercises: Vec<Entry>,
+    /// Alias to the name of the exercise it stands for
+    aliases: BTreeMap<String, String>,
+}
+
+/// How a name given by the user was found in the catalog
+pub enum Match<'a> {
+    Exact(&'a Entry),
+    /// Closest entry to a misspelt name
+    Fuzzy(&'a Entry),
+    Unknown,
+}
+
+pub struct Catalog {
+    path: PathBuf,
+    file: CatalogFile,
+    entries: Vec<Entry>,
+}
+
+impl Catalog {
+    /// Only the built-in exercises, with nowhere to save additions
+    #[cfg(test)]
+    pub fn built_in() -> Self {
+        let mut catalog = Catalog {
+            path: PathBuf::new(),
+            file: CatalogFile::default(),
+            entries: Vec::new(),
+        };
+        catalog.merge();
+        catalog
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let file = if path.exists() {
+            let file = File::open(path).context("Unable to read existing exercise catalog")?;
+            serde_json::from_reader(file)
+                .context("Failed converting exercise catalog contents into json")?
+        } else {
+            CatalogFile::default()
+        };
+        let mut catalog = Catalog {
+            path: path.to_owned(),
+            file,
+            entries: Vec::new(),
+        };
+        catalog.merge();
+        Ok(catalog)
+    }
+
+    fn save(&self) -> Result<()> {
+        persist::write_json_pretty(&self.path, &self.file)
+            .context("Failed writing to exercise catalog")
+    }
+
+    /// Rebuilds the entries from the built-in list and the user's additions
+    fn merge(&mut self) {
+        let mut entries: Vec<Entry> = BUILT_IN
+            .iter()
+            .map(|(name, primary, secondary, aliases)| Entry {
+                name: (*name).to_owned(),
+                primary: primary.iter().map(|m| (*m).to_owned()).collect(),
+                secondary: secondary.iter().map(|m| (*m).to_owned()).collect(),
+                aliases: aliases.iter().map(|a| (*a).to_owned()).collect(),
+                custom: false,
+            })
+            .collect();
+        for entry in &self.file.exercises {
+            let entry = Entry {
+                custom: true,
+                ..entry.clone()
+            };
+            match entries.iter_mut().find(|e| e.name == entry.name) {
+                Some(existing) => *existing = entry,
+                None => entries.push(entry),
+            }
+        }
+        for (alias, name) in &self.file.aliases {
+            if let Some(entry) = entries.iter_mut().find(|e| &e.name == name) {
+                entry.aliases.push(alias.clone());
+            }
+        }
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        self.entries = entries;
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    fn exact(&self, name: &str) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .find(|e| e.name == name || e.aliases.iter().any(|a| a == name))
+    }
+
+    /// Entry a logged exercise counts as, whether it was logged by name, alias or with a typo.
+    /// Every report crediting logged exercises to the catalog goes through this.
+    pub fn counts_as(&self, name: &str) -> Option<&Entry> {
+        match self.identify(name) {
+            Match::Exact(entry) | Match::Fuzzy(entry) => Some(entry),
+            Match::Unknown => None,
+        }
+    }
+
+    /// Looks up a name or alias, falling back to the only entry within a typo of it
+    pub fn identify(&self, name: &str) -> Match<'_> {
+        let name = exercise::normalize_name(name);
+        if let Some(entry) = self.exact(&name) {
+            return Match::Exact(entry);
+        }
+        let mut candidates: Vec<(usize, &Entry)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let distance = std::iter::once(&entry.name)
+                    .chain(&entry.aliases)
+                    .filter_map(|candidate| typo_distance(&name, candidate))
+                    .min()?;
+                Some((distance, entry))
+            })
+            .collect();
+        candidates.sort_by_key(|(distance, _)| *distance);
+        match candidates.as_slice() {
+            [(best, entry), rest @ ..] if rest.first().is_none_or(|(next, _)| next > best) => {
+                Match::Fuzzy(entry)
+            }
+            _ => Match::Unknown,
+        }
+    }
+
+    /// Names aliased exercises as in the catalog and returns the muscles the exercises train.
+    /// Misspelt exercises keep the name they were logged with and only borrow the muscles.
+    pub fn attribute(&self, exercises: &mut [Exercise]) -> Vec<String> {
+        let mut muscles: Vec<String> = Vec::new();
+        for exercise in exercises {
+            let entry = match self.identify(&exercise.name) {
+                Match::Exact(entry) => {
+                    exercise.name = entry.name.clone();
+                    entry
+                }
+                Match::Fuzzy(entry) => {
+                    println!(
+                        "Exercise {} is not in the catalog. Counting the muscles of {} for it.",
+                        exercise.name, entry.name
+                    );
+                    entry
+                }
+                Match::Unknown => {
+                    println!(
+                        "Exercise {} is not in the catalog. Add it with `exercise add` to record the muscles it trains.",
+                        exercise.name
+                    );
+                    continue;
+                }
+            };
+            for muscle in entry.muscles() {
+                if !muscles.contains(muscle) {
+                    muscles.push(muscle.clone());
+                }
+            }
+        }
+        muscles
+    }
+
+    pub fn add(&mut self, entry: Entry) -> Result<()> {
+        if entry.primary.is_empty() {
+            return Err(anyhow!(
+                "Exercise {} needs a primary muscle group",
+                entry.name
+            ));
+        }
+        for alias in &entry.aliases {
+            self.check_alias(alias, &entry.name)?;
+        }
+        let name = entry.name.clone();
+        let replaced = self.entries.iter().any(|e| e.name == name);
+        self.file.exercises.retain(|e| e.name != name);
+        self.file.exercises.push(entry);
+        self.merge();
+        self.save()?;
+        if replaced {
+            println!("Successfully updated exercise {name}");
+        } else {
+            println!("Successfully added exercise {name}");
+        }
+        Ok(())
+    }
+
+    pub fn alias(&mut self, alias: &str, name: &str) -> Result<()> {
+        let alias = exercise::normalize_name(alias);
+        let Some(entry) = self.exact(&exercise::normalize_name(name)) else {
+            return Err(anyhow!(
+                "Exercise {name} is not in the catalog. Review exercises with `exercise list`."
+            ));
+        };
+        let name = entry.name.clone();
+        self.check_alias(&alias, &name)?;
+        self.file.aliases.insert(alias.clone(), name.clone());
+        self.merge();
+        self.save()?;
+        println!("Successfully added alias {alias} for exercise {name}");
+        Ok(())
+    }
+
+    fn check_alias(&self, alias: &str, name: &str) -> Result<()> {
+        if alias.is_empty() {
+            return Err(anyhow!("Aliases must not be empty"));
+        }
+        if let Some(existing) = self.exact(alias)
+            && existing.name != name
+        {
+            return Err(anyhow!(
+                "{alias} already refers to exercise {}",
+                existing.name
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Edit distance between a name and a catalog name that differ only by typos: both have
+/// the same words, short words must match exactly and longer ones may have one typo per
+/// four characters
+fn typo_distance(name: &str, candidate: &str) -> Option<usize> {
+    let words: Vec<&str> = name.split_whitespace().collect();
+    let candidate_words: Vec<&str> = candidate.split_whitespace().collect();
+    if words.len() != candidate_words.len() {
+        return None;
+    }
+    let mut total = 0;
+    for (word, candidate_word) in words.iter().zip(&candidate_words) {
+        let length = word.chars().count();
+        let tolerance = if length <= 4 { 0 } else { length / 4 };
+        let distance = edit_distance(word, candidate_word);
+        if distance > tolerance {
+            return None;
+        }
+        total += distance;
+    }
+    Some(total)
+}
+
+/// Edit distance between two names counting insertions, deletions, substitutions and
+/// swaps of adjacent characters, the usual typos
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = distances[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = substitution
+                .min(distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identified(name: &str) -> Option<String> {
+        match Catalog::built_in().identify(name) {
+            Match::Exact(entry) | Match::Fuzzy(entry) => Some(entry.name.clone()),
+            Match::Unknown => None,
+        }
+    }
+
+    #[test]
+    fn edit_distance_counts_typos() {
+        assert_eq!(edit_distance("bench", "bench"), 0);
+        assert_eq!(edit_distance("bnech", "bench"), 1);
+        assert_eq!(edit_distance("bench", "benches"), 2);
+        assert_eq!(edit_distance("squat", "sqat"), 1);
+        assert_eq!(edit_distance("", "row"), 3);
+        assert_eq!(edit_distance("hack", "back"), 1);
+    }
+
+    #[test]
+    fn identify_matches_names_and_aliases_exactly() {
+        let catalog = Catalog::built_in();
+        assert!(
+            matches!(catalog.identify("Bench  Press"), Match::Exact(e) if e.name == "bench press")
+        );
+        assert!(matches!(catalog.identify("ohp"), Match::Exact(e) if e.name == "overhead press"));
+    }
+
+    #[test]
+    fn identify_tolerates_typos_in_long_words() {
+        assert_eq!(identified("bnech"), Some("bench press".to_owned()));
+        assert_eq!(identified("sqaut"), Some("squat".to_owned()));
+        assert_eq!(identified("incline bnech pres"), None);
+        assert_eq!(
+            identified("romanian deadlfit"),
+            Some("romanian deadlift".to_owned())
+        );
+    }
+
+    #[test]
+    fn identify_rejects_other_exercises() {
+        assert_eq!(identified("hack squat"), None);
+        assert_eq!(identified("zercher"), None);
+        assert_eq!(identified("row machine"), None);
+    }
+
+    #[test]
+    fn logged_names_count_as_their_entry() {
+        let catalog = Catalog::built_in();
+        let counts_as = |name| catalog.counts_as(name).map(|entry| entry.name.as_str());
+        assert_eq!(counts_as("bench press"), Some("bench press"));
+        assert_eq!(counts_as("flat bench"), Some("bench press"));
+        assert_eq!(counts_as("bnech"), Some("bench press"));
+        assert_eq!(counts_as("hack squat"), None);
+    }
+
+    #[test]
+    fn attribute_keeps_misspelt_names() {
+        let catalog = Catalog::built_in();
+        let mut exercises = vec![
+            exercise::parse("bnech 3x5@100kg").unwrap(),
+            exercise::parse("ohp 5@50kg").unwrap(),
+            exercise::parse("hack squat 3x8@100kg").unwrap(),
+        ];
+        let muscles = catalog.attribute(&mut exercises);
+        let names: Vec<&str> = exercises.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["bnech", "overhead press", "hack squat"]);
+        assert_eq!(muscles, ["chest", "triceps", "front-delts", "side-delts"]);
+    }
+}
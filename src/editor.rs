@@ -0,0 +1,82 @@
+use anyhow::{Context, Result, anyhow};
+use rand::{Rng, distr::Alphanumeric};
+use serde::{Serialize, de::DeserializeOwned};
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Write},
+    path::PathBuf,
+    process::Command,
+};
+
+/// Opens `value` as JSON in the user's editor and parses the result once the editor exits
+pub fn edit_json<T: Serialize + DeserializeOwned>(value: &T, name: &str) -> Result<T> {
+    let contents =
+        serde_json::to_string_pretty(value).context("Failed converting data into json")?;
+    let (path, mut file) = create_temp_file(name)?;
+    let written = file
+        .write_all(contents.as_bytes())
+        .and_then(|()| file.sync_all());
+    drop(file);
+    if let Err(error) = written {
+        let _ = fs::remove_file(&path);
+        return Err(error).context("Unable to write temporary file for editing");
+    }
+    let edited = run_editor(&path).and_then(|_| {
+        let contents =
+            fs::read_to_string(&path).context("Unable to read temporary file after editing")?;
+        serde_json::from_str(&contents).context("Edited contents are not valid")
+    });
+    let _ = fs::remove_file(&path);
+    edited
+}
+
+/// Creates a new file only the current user can read, with a random name so that no other
+/// user can predict it and plant a file or link there first
+fn create_temp_file(name: &str) -> Result<(PathBuf, File)> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    for _ in 0..10 {
+        let suffix: String = rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(12)
+            .map(char::from)
+            .collect();
+        let path = env::temp_dir().join(format!("wr-{name}-{suffix}.json"));
+        match options.open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(error) if error.kind() == ErrorKind::AlreadyExists => continue,
+            Err(error) => {
+                return Err(error).context("Unable to create temporary file for editing");
+            }
+        }
+    }
+    Err(anyhow!(
+        "Unable to create temporary file for editing with an unused name"
+    ))
+}
+
+fn run_editor(path: &std::path::Path) -> Result<()> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| default_editor().to_owned());
+    let mut words = editor.split_whitespace();
+    let program = words
+        .next()
+        .ok_or_else(|| anyhow!("The EDITOR environment variable is empty"))?;
+    let status = Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Unable to launch editor `{editor}`"))?;
+    if !status.success() {
+        return Err(anyhow!("Editor `{editor}` exited with {status}"));
+    }
+    Ok(())
+}
+
+fn default_editor() -> &'static str {
+    if cfg!(windows) { "notepad" } else { "vi" }
+}
@@ -0,0 +1,259 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+
+const POUNDS_PER_KILOGRAM: f64 = 2.204_622_621_8;
+/// Most sets a single group may repeat, far beyond any real workout
+const MAX_SETS: usize = 100;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WeightUnit {
+    Kg,
+    Lb,
+}
+
+impl Display for WeightUnit {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            WeightUnit::Kg => write!(f, "kg"),
+            WeightUnit::Lb => write!(f, "lb"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Weight {
+    pub value: f64,
+    pub unit: WeightUnit,
+}
+
+impl Weight {
+    pub fn kilograms(&self) -> f64 {
+        match self.unit {
+            WeightUnit::Kg => self.value,
+            WeightUnit::Lb => self.value / POUNDS_PER_KILOGRAM,
+        }
+    }
+}
+
+impl Display for Weight {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.value, self.unit)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Set {
+    pub reps: u32,
+    #[serde(default)]
+    pub weight: Option<Weight>,
+    #[serde(default)]
+    pub rpe: Option<f64>,
+    #[serde(default)]
+    pub rir: Option<u32>,
+}
+
+impl Set {
+    /// Reps times weight in kilograms, zero for bodyweight sets
+    pub fn volume(&self) -> f64 {
+        self.weight
+            .map(|weight| f64::from(self.reps) * weight.kilograms())
+            .unwrap_or_default()
+    }
+}
+
+impl Display for Set {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.reps)?;
+        if let Some(weight) = self.weight {
+            write!(f, "@{weight}")?;
+        }
+        if let Some(rpe) = self.rpe {
+            write!(f, " rpe{rpe}")?;
+        }
+        if let Some(rir) = self.rir {
+            write!(f, " rir{rir}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Exercise {
+    pub name: String,
+    pub sets: Vec<Set>,
+}
+
+impl Exercise {
+    /// Total volume in kilograms lifted
+    pub fn volume(&self) -> f64 {
+        self.sets.iter().map(Set::volume).sum()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(anyhow!("Exercise names must not be empty"));
+        }
+        if self.sets.is_empty() {
+            return Err(anyhow!("Exercise {} has no sets", self.name));
+        }
+        for set in &self.sets {
+            if set.reps == 0 {
+                return Err(anyhow!("Sets of {} must have at least one rep", self.name));
+            }
+            if set
+                .weight
+                .is_some_and(|weight| !weight.value.is_finite() || weight.value < 0.0)
+            {
+                return Err(anyhow!("Weights of {} must not be negative", self.name));
+            }
+            if set.rpe.is_some_and(|rpe| !(1.0..=10.0).contains(&rpe)) {
+                return Err(anyhow!("RPE of {} must be between 1 and 10", self.name));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Renders consecutive identical sets compactly, as in `bench 3x5@100kg, 1x3@110kg`
+impl Display for Exercise {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        let mut groups: Vec<(usize, &Set)> = Vec::new();
+        for set in &self.sets {
+            match groups.last_mut() {
+                Some((count, last)) if *last == set => *count += 1,
+                _ => groups.push((1, set)),
+            }
+        }
+        for (index, (count, set)) in groups.iter().enumerate() {
+            let separator = if index == 0 { " " } else { ", " };
+            write!(f, "{separator}{count}x{set}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the compact exercise syntax: a name followed by comma separated groups of sets,
+/// each written as `[sets x] reps [@ weight unit] [rpe N] [rir N]`.
+/// For example `bench 3x5@100kg`, `dips 3x10` or `squat 5@100kg rpe7, 3x3@120kg rir1`.
+pub fn parse(input: &str) -> Result<Exercise> {
+    let input = input.trim();
+    let invalid = || {
+        anyhow!(
+            "Unable to understand the exercise '{input}'. \
+             Use for example \"bench 3x5@100kg\" or \"dips 3x10\"."
+        )
+    };
+    let sets_start = input
+        .char_indices()
+        .find(|(index, c)| {
+            c.is_ascii_digit() && (*index == 0 || input[..*index].ends_with(char::is_whitespace))
+        })
+        .map(|(index, _)| index)
+        .ok_or_else(invalid)?;
+    let name = normalize_name(&input[..sets_start]);
+    if name.is_empty() {
+        return Err(invalid());
+    }
+    let mut sets = Vec::new();
+    for group in input[sets_start..].split(',') {
+        let (count, set) = parse_group(group.trim()).map_err(|error| {
+            anyhow!(
+                "Unable to understand the sets '{}' of '{input}': {error}",
+                group.trim()
+            )
+        })?;
+        sets.extend(std::iter::repeat_n(set, count));
+    }
+    let exercise = Exercise { name, sets };
+    exercise.validate()?;
+    Ok(exercise)
+}
+
+pub fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn parse_group(group: &str) -> Result<(usize, Set)> {
+    let mut tokens = group.split_whitespace();
+    let scheme = tokens.next().ok_or_else(|| anyhow!("missing sets"))?;
+    let (scheme, weight) = match scheme.split_once('@') {
+        Some((scheme, weight)) => (scheme, Some(parse_weight(weight)?)),
+        None => (scheme, None),
+    };
+    let (count, reps) = match scheme.split_once(['x', 'X']) {
+        Some((count, reps)) => (count.parse()?, reps.parse()?),
+        None => (1, scheme.parse()?),
+    };
+    if !(1..=MAX_SETS).contains(&count) {
+        return Err(anyhow!("number of sets must be between 1 and {MAX_SETS}"));
+    }
+    let mut set = Set {
+        reps,
+        weight,
+        rpe: None,
+        rir: None,
+    };
+    for token in tokens {
+        let token = token.to_lowercase();
+        if let Some(rpe) = token.strip_prefix("rpe") {
+            set.rpe = Some(rpe.parse()?);
+        } else if let Some(rir) = token.strip_prefix("rir") {
+            set.rir = Some(rir.parse()?);
+        } else {
+            return Err(anyhow!("unexpected '{token}'"));
+        }
+    }
+    Ok((count, set))
+}
+
+fn parse_weight(weight: &str) -> Result<Weight> {
+    let weight = weight.to_lowercase();
+    let (value, unit) = if let Some(value) = weight.strip_suffix("kg") {
+        (value, WeightUnit::Kg)
+    } else if let Some(value) = weight
+        .strip_suffix("lbs")
+        .or_else(|| weight.strip_suffix("lb"))
+    {
+        (value, WeightUnit::Lb)
+    } else {
+        return Err(anyhow!("weight needs a unit of kg or lb"));
+    };
+    Ok(Weight {
+        value: value.parse()?,
+        unit,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_repeats_groups_of_sets() {
+        let exercise = parse("Bench  Press 3x5@100kg, 8@80kg rpe7").expect("valid exercise");
+        assert_eq!(exercise.name, "bench press");
+        assert_eq!(exercise.sets.len(), 4);
+        assert_eq!(exercise.sets[3].reps, 8);
+        assert_eq!(exercise.sets[3].rpe, Some(7.0));
+    }
+
+    #[test]
+    fn parse_bounds_the_number_of_sets() {
+        assert_eq!(
+            parse("dips 100x10").expect("valid exercise").sets.len(),
+            100
+        );
+        for input in ["dips 101x10", "dips 0x5", "dips 99999999999x1"] {
+            let error = parse(input)
+                .expect_err("set count out of range")
+                .to_string();
+            assert!(error.contains("Unable to understand the sets"), "{error}");
+        }
+    }
+}
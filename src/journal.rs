@@ -0,0 +1,152 @@
+use crate::{Session, Storage, persist};
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    fs::File,
+    path::Path,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct JournalSettings {
+    /// Number of operations that can be undone
+    pub history_limit: usize,
+}
+
+impl Default for JournalSettings {
+    fn default() -> Self {
+        JournalSettings { history_limit: 50 }
+    }
+}
+
+/// A single mutation of the stored sessions, holding enough to revert it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+pub enum Operation {
+    Add { session: Session },
+    Remove { session: Session },
+    Edit { before: Session, after: Session },
+}
+
+impl Operation {
+    fn apply(&self, storage: &mut Storage) -> Result<()> {
+        match self {
+            Operation::Add { session } => insert_new(storage, session),
+            Operation::Remove { session } => remove_existing(storage, &session.identifier),
+            Operation::Edit { after, .. } => replace(storage, after),
+        }
+    }
+
+    fn revert(&self, storage: &mut Storage) -> Result<()> {
+        match self {
+            Operation::Add { session } => remove_existing(storage, &session.identifier),
+            Operation::Remove { session } => insert_new(storage, session),
+            Operation::Edit { before, .. } => replace(storage, before),
+        }
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Operation::Add { session } => {
+                write!(f, "adding workout session {}", session.identifier)
+            }
+            Operation::Remove { session } => {
+                write!(f, "removing workout session {}", session.identifier)
+            }
+            Operation::Edit { after, .. } => {
+                write!(f, "editing workout session {}", after.identifier)
+            }
+        }
+    }
+}
+
+fn insert_new(storage: &mut Storage, session: &Session) -> Result<()> {
+    if storage.position(&session.identifier).is_ok() {
+        return Err(anyhow!(
+            "Workout session {} already exists",
+            session.identifier
+        ));
+    }
+    storage.insert(session.clone());
+    Ok(())
+}
+
+fn remove_existing(storage: &mut Storage, identifier: &str) -> Result<()> {
+    let index = storage.position(identifier)?;
+    storage.take(index);
+    Ok(())
+}
+
+fn replace(storage: &mut Storage, session: &Session) -> Result<()> {
+    remove_existing(storage, &session.identifier)?;
+    storage.insert(session.clone());
+    Ok(())
+}
+
+/// Bounded history of operations kept next to the storage file for `undo` and `redo`
+#[derive(Serialize, Deserialize, Default)]
+pub struct Journal {
+    undo: Vec<Operation>,
+    redo: Vec<Operation>,
+    #[serde(skip)]
+    pub dirty: bool,
+}
+
+impl Journal {
+    pub fn read(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Journal::default());
+        }
+        let file = File::open(path).context("Unable to read existing journal file")?;
+        serde_json::from_reader(file).context("Failed converting journal file contents into json")
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        persist::write_json(path, &self).context("Failed writing to journal file when saving")
+    }
+
+    /// Records new operations, discarding anything that could be redone
+    pub fn record(&mut self, operations: Vec<Operation>, history_limit: usize) {
+        if operations.is_empty() {
+            return;
+        }
+        self.dirty = true;
+        self.redo.clear();
+        self.undo.extend(operations);
+        let excess = self.undo.len().saturating_sub(history_limit);
+        self.undo.drain(..excess);
+    }
+
+    pub fn undo(&mut self, storage: &mut Storage) -> Result<()> {
+        let operation = self
+            .undo
+            .pop()
+            .ok_or_else(|| anyhow!("There is nothing to undo"))?;
+        if let Err(error) = operation.revert(storage) {
+            self.undo.push(operation);
+            return Err(error.context("Unable to undo the last operation"));
+        }
+        println!("Undid {operation}");
+        self.dirty = true;
+        self.redo.push(operation);
+        Ok(())
+    }
+
+    pub fn redo(&mut self, storage: &mut Storage) -> Result<()> {
+        let operation = self
+            .redo
+            .pop()
+            .ok_or_else(|| anyhow!("There is nothing to redo"))?;
+        if let Err(error) = operation.apply(storage) {
+            self.redo.push(operation);
+            return Err(error.context("Unable to redo the last undone operation"));
+        }
+        println!("Redid {operation}");
+        self.dirty = true;
+        self.undo.push(operation);
+        Ok(())
+    }
+}
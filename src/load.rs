@@ -0,0 +1,310 @@
+use chrono::{NaiveDate, TimeDelta};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct LoadSettings {
+    /// Decay time constant of chronic fitness, in days
+    pub fitness_time_constant: f64,
+    /// Decay time constant of acute fatigue, in days
+    pub fatigue_time_constant: f64,
+    pub fitness_weight: f64,
+    pub fatigue_weight: f64,
+    /// Load of a session without a recorded RPE
+    pub default_session_load: f64,
+}
+
+impl Default for LoadSettings {
+    fn default() -> Self {
+        LoadSettings {
+            fitness_time_constant: 42.0,
+            fatigue_time_constant: 7.0,
+            fitness_weight: 1.0,
+            fatigue_weight: 1.0,
+            default_session_load: 5.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DailyLoad {
+    pub date: NaiveDate,
+    pub load: f64,
+    pub fitness: f64,
+    pub fatigue: f64,
+    pub form: f64,
+}
+
+/// Banister impulse-response model, turning daily training loads into fitness and fatigue
+pub struct LoadModel {
+    settings: LoadSettings,
+}
+
+impl LoadModel {
+    pub fn new(settings: &LoadSettings) -> Self {
+        LoadModel {
+            settings: settings.clone(),
+        }
+    }
+
+    pub fn session_load(&self, rpe: Option<u8>) -> f64 {
+        rpe.map(f64::from)
+            .unwrap_or(self.settings.default_session_load)
+    }
+
+    /// Runs the model day by day from the first load up to and including `until`.
+    /// Loads dated after `until` are ignored.
+    pub fn daily(&self, loads: &[(NaiveDate, f64)], until: NaiveDate) -> Vec<DailyLoad> {
+        let per_day = per_day(loads, until);
+        let Some(first) = per_day.keys().next().copied() else {
+            return Vec::new();
+        };
+        let fitness_decay = decay(self.settings.fitness_time_constant);
+        let fatigue_decay = decay(self.settings.fatigue_time_constant);
+        let (mut fitness, mut fatigue) = (0.0, 0.0);
+        first
+            .iter_days()
+            .take_while(|date| *date <= until)
+            .map(|date| {
+                let load = per_day.get(&date).copied().unwrap_or_default();
+                fitness = fitness * fitness_decay + load;
+                fatigue = fatigue * fatigue_decay + load;
+                DailyLoad {
+                    date,
+                    load,
+                    fitness,
+                    fatigue,
+                    form: self.settings.fitness_weight * fitness
+                        - self.settings.fatigue_weight * fatigue,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Sums loads per day, ignoring any dated after `until`
+fn per_day(loads: &[(NaiveDate, f64)], until: NaiveDate) -> BTreeMap<NaiveDate, f64> {
+    let mut per_day: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    for (date, load) in loads.iter().filter(|(date, _)| *date <= until) {
+        *per_day.entry(*date).or_default() += load;
+    }
+    per_day
+}
+
+fn decay(time_constant: f64) -> f64 {
+    if time_constant > 0.0 {
+        (-1.0 / time_constant).exp()
+    } else {
+        0.0
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AcwrMethod {
+    /// Plain averages over the acute and chronic windows
+    Rolling,
+    /// Exponentially weighted moving averages with spans of the acute and chronic windows
+    Ewma,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AcwrSettings {
+    pub method: AcwrMethod,
+    pub acute_days: u32,
+    pub chronic_days: u32,
+    /// Ratio above which `add` and `list` print a warning
+    pub danger_threshold: f64,
+}
+
+impl Default for AcwrSettings {
+    fn default() -> Self {
+        AcwrSettings {
+            method: AcwrMethod::Rolling,
+            acute_days: 7,
+            chronic_days: 28,
+            danger_threshold: 1.5,
+        }
+    }
+}
+
+impl AcwrSettings {
+    /// Acute:chronic workload ratio on the given day, or `None` until the loads cover the
+    /// whole chronic window or without any chronic load
+    pub fn ratio(&self, loads: &[(NaiveDate, f64)], on: NaiveDate) -> Option<f64> {
+        let window_start =
+            on.checked_sub_signed(TimeDelta::days(i64::from(self.chronic_days.max(1)) - 1))?;
+        let earliest = loads.iter().map(|(date, _)| *date).min()?;
+        if earliest > window_start {
+            return None;
+        }
+        let (acute, chronic) = match self.method {
+            AcwrMethod::Rolling => (
+                rolling_average(loads, on, self.acute_days),
+                rolling_average(loads, on, self.chronic_days),
+            ),
+            AcwrMethod::Ewma => (
+                ewma(loads, on, self.acute_days),
+                ewma(loads, on, self.chronic_days),
+            ),
+        };
+        (chronic > 0.0).then(|| acute / chronic)
+    }
+
+    pub fn is_dangerous(&self, ratio: f64) -> bool {
+        ratio > self.danger_threshold
+    }
+}
+
+fn rolling_average(loads: &[(NaiveDate, f64)], on: NaiveDate, days: u32) -> f64 {
+    if days == 0 {
+        return 0.0;
+    }
+    let first = on - TimeDelta::days(i64::from(days) - 1);
+    let total: f64 = loads
+        .iter()
+        .filter(|(date, _)| (first..=on).contains(date))
+        .map(|(_, load)| load)
+        .sum();
+    total / f64::from(days)
+}
+
+fn ewma(loads: &[(NaiveDate, f64)], on: NaiveDate, span: u32) -> f64 {
+    let per_day = per_day(loads, on);
+    let Some(first) = per_day.keys().next().copied() else {
+        return 0.0;
+    };
+    let lambda = 2.0 / (f64::from(span) + 1.0);
+    first
+        .iter_days()
+        .take_while(|date| *date <= on)
+        .fold(0.0, |average, date| {
+            let load = per_day.get(&date).copied().unwrap_or_default();
+            load * lambda + (1.0 - lambda) * average
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(date: &str) -> NaiveDate {
+        date.parse().expect("test date")
+    }
+
+    /// A load on every day from `first` to `last`, heavier in the last `heavy_days`
+    fn daily_loads(first: &str, last: &str, heavy_days: i64) -> Vec<(NaiveDate, f64)> {
+        let last = date(last);
+        date(first)
+            .iter_days()
+            .take_while(|day| *day <= last)
+            .map(|day| {
+                let heavy = last - day < TimeDelta::days(heavy_days);
+                (day, if heavy { 2.0 } else { 1.0 })
+            })
+            .collect()
+    }
+
+    fn model() -> LoadModel {
+        LoadModel::new(&LoadSettings {
+            fitness_weight: 1.5,
+            fatigue_weight: 2.0,
+            ..LoadSettings::default()
+        })
+    }
+
+    #[test]
+    fn a_single_load_decays_every_day() {
+        let days = model().daily(&[(date("2026-10-01"), 10.0)], date("2026-10-04"));
+        let dates: Vec<NaiveDate> = days.iter().map(|day| day.date).collect();
+        assert_eq!(
+            dates,
+            ["2026-10-01", "2026-10-02", "2026-10-03", "2026-10-04"].map(date)
+        );
+        for (elapsed, day) in days.iter().enumerate() {
+            let elapsed = elapsed as f64;
+            assert!((day.fitness - 10.0 * (-elapsed / 42.0).exp()).abs() < 1e-9);
+            assert!((day.fatigue - 10.0 * (-elapsed / 7.0).exp()).abs() < 1e-9);
+        }
+        assert_eq!(days[0].load, 10.0);
+        assert_eq!(days[3].load, 0.0);
+    }
+
+    #[test]
+    fn daily_ignores_loads_after_until() {
+        let loads = [(date("2026-10-01"), 6.0), (date("2026-10-03"), 9.0)];
+        let days = model().daily(&loads, date("2026-10-02"));
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[1].load, 0.0);
+        assert!(model().daily(&loads[1..], date("2026-10-02")).is_empty());
+    }
+
+    #[test]
+    fn daily_sums_loads_on_the_same_day() {
+        let loads = [
+            (date("2026-10-01"), 6.0),
+            (date("2026-10-02"), 4.0),
+            (date("2026-10-01"), 3.0),
+        ];
+        let days = model().daily(&loads, date("2026-10-02"));
+        assert_eq!(days[0].load, 9.0);
+        assert_eq!(days[0].fitness, 9.0);
+        assert_eq!(days[1].load, 4.0);
+    }
+
+    #[test]
+    fn form_weighs_fitness_against_fatigue() {
+        let loads = [(date("2026-10-01"), 8.0), (date("2026-10-05"), 5.0)];
+        for day in model().daily(&loads, date("2026-10-20")) {
+            assert!((day.form - (1.5 * day.fitness - 2.0 * day.fatigue)).abs() < 1e-9);
+        }
+    }
+
+    fn settings(method: AcwrMethod) -> AcwrSettings {
+        AcwrSettings {
+            method,
+            ..AcwrSettings::default()
+        }
+    }
+
+    #[test]
+    fn rolling_ratio_needs_a_full_chronic_window() {
+        let acwr = settings(AcwrMethod::Rolling);
+        let on = date("2026-10-28");
+        let covered = daily_loads("2026-10-01", "2026-10-28", 7);
+        let ratio = acwr.ratio(&covered, on).expect("history covers 28 days");
+        assert!((ratio - 2.0 / 1.25).abs() < 1e-9);
+        let short = daily_loads("2026-10-02", "2026-10-28", 7);
+        assert_eq!(acwr.ratio(&short, on), None);
+        assert_eq!(acwr.ratio(&[], on), None);
+    }
+
+    #[test]
+    fn ewma_ratio_needs_a_full_chronic_window() {
+        let acwr = settings(AcwrMethod::Ewma);
+        let on = date("2026-10-28");
+        let covered = daily_loads("2026-10-01", "2026-10-28", 7);
+        let ratio = acwr.ratio(&covered, on).expect("history covers 28 days");
+        assert!(ratio > 1.0);
+        let short = daily_loads("2026-10-02", "2026-10-28", 7);
+        assert_eq!(acwr.ratio(&short, on), None);
+    }
+
+    #[test]
+    fn ratio_needs_a_chronic_load() {
+        let loads = [(date("2026-09-01"), 8.0)];
+        let acwr = settings(AcwrMethod::Rolling);
+        assert_eq!(acwr.ratio(&loads, date("2026-10-28")), None);
+    }
+
+    #[test]
+    fn ratio_ignores_later_loads() {
+        let acwr = settings(AcwrMethod::Rolling);
+        let mut loads = daily_loads("2026-10-01", "2026-10-28", 0);
+        loads.push((date("2026-10-29"), 50.0));
+        let ratio = acwr.ratio(&loads, date("2026-10-28")).expect("covered");
+        assert!((ratio - 1.0).abs() < 1e-9);
+    }
+}
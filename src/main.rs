@@ -1,100 +1,377 @@
 use anyhow::{Context, Result, anyhow};
-use chrono::{DateTime, Utc};
+use backend::{Backend, BackendKind};
+use catalog::Catalog;
+use chrono::{DateTime, Datelike, Local, Months, NaiveDate, TimeDelta, Utc};
 use clap::{
-    Arg, ArgAction, ArgMatches, Command, builder::NonEmptyStringValueParser, command, value_parser,
+    Arg, ArgAction, ArgMatches, Command, builder::NonEmptyStringValueParser, command,
+    parser::ValueSource, value_parser,
 };
 use directories::ProjectDirs;
+use exercise::Exercise;
+use journal::{Journal, JournalSettings, Operation};
+use load::{AcwrSettings, LoadModel, LoadSettings};
+use output::{Format, Table};
+use persist::LockSettings;
+use profile::{DEFAULT_PROFILE, Profiles};
+use query::Query;
 use rand::{Rng, distr::Alphanumeric};
+use records::RecordSettings;
+use recovery::{Intensity, RecoveryModel, RecoverySettings};
 use serde::{Deserialize, Serialize};
-use serde_json;
+use serde_json::json;
 use std::{
     cmp,
+    collections::HashMap,
     fmt::{self, Display, Formatter},
     fs::{self, File},
+    io::{self, IsTerminal},
+    mem,
     path::{Path, PathBuf},
-    time::SystemTime,
+    time::Duration,
 };
+use timestamp::Timestamp;
+
+mod analytics;
+mod backend;
+mod calendar;
+mod catalog;
+mod editor;
+mod exercise;
+mod journal;
+mod load;
+mod migrate;
+mod output;
+mod persist;
+mod profile;
+mod query;
+mod records;
+mod recovery;
+mod timestamp;
 
 struct Config {
     storage_path: PathBuf,
+    database_path: PathBuf,
+    settings_path: PathBuf,
+    catalog_path: PathBuf,
+    journal_path: PathBuf,
+    lock_path: PathBuf,
+    settings: Settings,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Settings {
+    /// Data file location, relative to the profile's directory unless absolute
+    data_file: Option<PathBuf>,
+    backend: BackendKind,
+    recovery: RecoverySettings,
+    load: LoadSettings,
+    acwr: AcwrSettings,
+    records: RecordSettings,
+    journal: JournalSettings,
+    lock: LockSettings,
 }
 
 impl Config {
-    fn setup() -> Result<Self> {
+    fn directory() -> Result<PathBuf> {
         let dirs = ProjectDirs::from("", "", "workout-recovery-data")
             .context("Unable to determine path for local storage")?;
-        let storage_directory = Path::new(dirs.config_dir());
-        fs::create_dir_all(storage_directory)
+        let config_directory = dirs.config_dir().to_owned();
+        fs::create_dir_all(&config_directory)
             .context("Failed to create directories for local storage")?;
-        let storage_path = storage_directory.join("workout-recovery.json");
-        if !Path::exists(&storage_path) {
-            let default_data = Storage {
-                sessions: Vec::new(),
-            };
-            let new_file = File::create(&storage_path)
-                .context("Unable to create a new file for local storage")?;
-            serde_json::to_writer(new_file, &default_data)
-                .context("Failed writing to newly created local file")?;
+        Ok(config_directory)
+    }
+    fn setup(matches: &ArgMatches) -> Result<Self> {
+        let profiles = Profiles::new(&Config::directory()?);
+        let requested = matches.get_one::<String>("profile").map(String::as_str);
+        let (profile, profile_directory) = profiles.resolve(requested)?;
+        let config_directory = profile_directory.as_path();
+        let settings_path = config_directory.join("workout-recovery-config.json");
+        if !Path::exists(&settings_path) {
+            persist::write_json_pretty(&settings_path, &Settings::default())
+                .context("Unable to create a new file for configuration")?;
+        };
+        let catalog_path = config_directory.join("workout-recovery-exercises.json");
+        let settings_file =
+            File::open(&settings_path).context("Unable to read existing configuration file")?;
+        let settings: Settings = serde_json::from_reader(settings_file)
+            .context("Failed converting configuration file contents into json")?;
+        // `--data-file` takes precedence over `WR_DATA`, which clap reads into the same
+        // argument, followed by the configuration file and finally the default location.
+        // `WR_DATA` only moves the default profile's data, so other profiles never share it.
+        let data_file = matches.get_one::<PathBuf>("data-file").filter(|_| {
+            profile == DEFAULT_PROFILE
+                || matches.value_source("data-file") != Some(ValueSource::EnvVariable)
+        });
+        let storage_path = match (data_file, &settings.data_file) {
+            (Some(data_file), _) => data_file.to_owned(),
+            (None, Some(data_file)) => config_directory.join(data_file),
+            (None, None) => config_directory.join("workout-recovery.json"),
         };
-        Ok(Config { storage_path })
+        let storage_directory = storage_path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        fs::create_dir_all(storage_directory)
+            .context("Failed to create directories for local storage")?;
+        let stem = storage_path
+            .file_stem()
+            .context("Data file path must end with a file name")?
+            .to_string_lossy();
+        let journal_path = storage_directory.join(format!("{stem}-journal.json"));
+        let lock_path = storage_directory.join(format!("{stem}.lock"));
+        let database_path = storage_directory.join(format!("{stem}.sqlite3"));
+        Ok(Config {
+            storage_path,
+            database_path,
+            settings_path,
+            catalog_path,
+            journal_path,
+            lock_path,
+            settings,
+        })
+    }
+    fn backend(&self, kind: BackendKind) -> Box<dyn Backend> {
+        backend::open(kind, &self.storage_path, &self.database_path)
+    }
+    fn save_settings(&self) -> Result<()> {
+        persist::write_json_pretty(&self.settings_path, &self.settings)
+            .context("Failed writing to configuration file")
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct Session {
     identifier: String,
     description: String,
-    timestamp: SystemTime,
+    #[serde(deserialize_with = "timestamp::deserialize")]
+    timestamp: Timestamp,
+    #[serde(default)]
+    muscles: Vec<String>,
+    #[serde(default)]
+    rpe: Option<u8>,
+    #[serde(default)]
+    volume: Option<f64>,
+    #[serde(default, deserialize_with = "timestamp::deserialize_option")]
+    end: Option<Timestamp>,
+    #[serde(default)]
+    exercises: Vec<Exercise>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl Session {
+    fn new(description: &str, timestamp: Timestamp) -> Self {
+        Session {
+            identifier: String::new(),
+            description: description.to_owned(),
+            timestamp,
+            muscles: Vec::new(),
+            rpe: None,
+            volume: None,
+            end: None,
+            exercises: Vec::new(),
+            tags: Vec::new(),
+        }
+    }
+    fn intensity(&self) -> Intensity {
+        Intensity {
+            rpe: self.rpe,
+            volume: self.volume.or_else(|| self.exercise_volume()),
+        }
+    }
+    /// Total volume of the logged exercises, in kilograms lifted
+    fn exercise_volume(&self) -> Option<f64> {
+        let volume: f64 = self.exercises.iter().map(Exercise::volume).sum();
+        (volume > 0.0).then_some(volume)
+    }
+    /// Recovery is measured from the end of a timed session, otherwise from when it was added
+    fn finished_at(&self) -> Timestamp {
+        self.end.unwrap_or(self.timestamp)
+    }
+    fn validate(&self) -> Result<()> {
+        if self.description.trim().is_empty() {
+            return Err(anyhow!("Description must not be empty"));
+        }
+        if let Some(rpe) = self.rpe
+            && !(1..=10).contains(&rpe)
+        {
+            return Err(anyhow!("RPE must be between 1 and 10"));
+        }
+        if self
+            .volume
+            .is_some_and(|volume| !volume.is_finite() || volume < 0.0)
+        {
+            return Err(anyhow!("Volume must be a non-negative number"));
+        }
+        if self.end.is_some_and(|end| end < self.timestamp) {
+            return Err(anyhow!("End of the session must not be before its start"));
+        }
+        for exercise in &self.exercises {
+            exercise.validate()?;
+        }
+        Ok(())
+    }
+    fn duration(&self) -> Option<TimeDelta> {
+        Some(self.end? - self.timestamp)
+    }
 }
 
 impl Display for Session {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        let timestamp: DateTime<Utc> = self.timestamp.into();
-        let duration = Utc::now() - timestamp;
-        let delta_days = duration.num_days();
-        let delta_hours = duration.num_hours() % 24;
-        let delta_minutes = duration.num_minutes() % 60;
         writeln!(f, "{:>15} {}", "[Identifier]", self.identifier)?;
         writeln!(f, "{:>15} {}", "[Description]", self.description)?;
+        if !self.muscles.is_empty() {
+            writeln!(f, "{:>15} {}", "[Muscles]", self.muscles.join(", "))?;
+        }
+        if !self.tags.is_empty() {
+            writeln!(f, "{:>15} {}", "[Tags]", self.tags.join(", "))?;
+        }
+        if let Some(rpe) = self.rpe {
+            writeln!(f, "{:>15} {}", "[RPE]", rpe)?;
+        }
+        if let Some(volume) = self.volume {
+            writeln!(f, "{:>15} {}", "[Volume]", volume)?;
+        }
+        for (index, exercise) in self.exercises.iter().enumerate() {
+            let label = if index == 0 { "[Exercises]" } else { "" };
+            writeln!(f, "{label:>15} {exercise}")?;
+        }
+        if let Some(duration) = self.duration() {
+            writeln!(f, "{:>15} {}", "[Duration]", format_duration(duration))?;
+        }
         writeln!(
             f,
-            "{:>15} Days: {} | Hours: {} | Minutes: {}",
-            "[Time Elapsed]", delta_days, delta_hours, delta_minutes,
+            "{:>15} {}",
+            "[Time Elapsed]",
+            format_elapsed(time_elapsed(self.finished_at()))
         )
     }
 }
 
+fn time_elapsed(timestamp: Timestamp) -> TimeDelta {
+    Utc::now() - timestamp.to_utc()
+}
+
+fn format_elapsed(duration: TimeDelta) -> String {
+    let delta_days = duration.num_days();
+    let delta_hours = duration.num_hours() % 24;
+    let delta_minutes = duration.num_minutes() % 60;
+    format!("Days: {delta_days} | Hours: {delta_hours} | Minutes: {delta_minutes}")
+}
+
+fn format_duration(duration: TimeDelta) -> String {
+    let hours = duration.num_hours();
+    let minutes = duration.num_minutes() % 60;
+    format!("Hours: {hours} | Minutes: {minutes}")
+}
+
 #[derive(Serialize, Deserialize)]
 struct Storage {
+    version: u32,
     sessions: Vec<Session>,
+    /// Session started with `start` and not yet stopped
+    #[serde(default)]
+    active: Option<Session>,
+    /// Operations made since reading, to be recorded in the journal
+    #[serde(skip)]
+    changes: Vec<Operation>,
+    /// Whether anything changed since reading, so that read-only commands never save
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Storage {
+            version: migrate::CURRENT_VERSION,
+            sessions: Vec::new(),
+            active: None,
+            changes: Vec::new(),
+            dirty: false,
+        }
+    }
 }
 
 impl Storage {
-    fn read(config: &Config) -> Result<Self> {
-        let file =
-            File::open(&config.storage_path).context("Unable to read existing local file")?;
-        let storage: Storage = serde_json::from_reader(file)
-            .context("Failed converting local file contents into json")?;
-        Ok(storage)
-    }
-    fn save(&self, config: &Config) -> Result<()> {
-        let file = File::create(&config.storage_path)
-            .context("Unable to open existing storage file when saving")?;
-        serde_json::to_writer(file, &self)
-            .context("Failed writing to existing storage file when saving")?;
+    fn add(&mut self, mut session: Session, records: &RecordSettings) {
+        session.identifier = new_id(self);
+        println!(
+            "Adding new workout session with identifier {} ...",
+            session.identifier
+        );
+        self.changes.push(Operation::Add {
+            session: session.clone(),
+        });
+        let records = records::detect(&self.sessions, &session, records);
+        self.insert(session);
+        println!("Successfully added new workout session");
+        announce_records(&records);
+    }
+    fn insert(&mut self, session: Session) {
+        self.dirty = true;
+        let index = self
+            .sessions
+            .partition_point(|s| s.timestamp <= session.timestamp);
+        self.sessions.insert(index, session);
+    }
+    fn take(&mut self, index: usize) -> Session {
+        self.dirty = true;
+        self.sessions.remove(index)
+    }
+    fn start(&mut self, mut session: Session) -> Result<()> {
+        if let Some(active) = &self.active {
+            return Err(anyhow!(
+                "Workout session {} is already in progress. End it with `stop` command.",
+                active.identifier
+            ));
+        }
+        session.identifier = new_id(self);
+        println!(
+            "Started new workout session with identifier {}",
+            session.identifier
+        );
+        self.dirty = true;
+        self.active = Some(session);
         Ok(())
     }
-    fn add(&mut self, description: &str) {
-        let identifier = new_id(self);
-        println!("Adding new workout session with identifier {identifier} ...");
-        self.sessions.push(Session {
-            identifier: identifier,
-            description: description.to_owned(),
-            timestamp: SystemTime::now(),
+    fn stop(
+        &mut self,
+        intensity: Intensity,
+        exercises: Vec<Exercise>,
+        muscles: Vec<String>,
+        records: &RecordSettings,
+    ) -> Result<()> {
+        let Some(mut session) = self.active.take() else {
+            return Err(anyhow!(
+                "No workout session is in progress. Begin one with `start` command."
+            ));
+        };
+        self.dirty = true;
+        session.end = Some(timestamp::now());
+        session.rpe = intensity.rpe.or(session.rpe);
+        session.volume = intensity.volume.or(session.volume);
+        session.exercises.extend(exercises);
+        for muscle in muscles {
+            if !session.muscles.contains(&muscle) {
+                session.muscles.push(muscle);
+            }
+        }
+        let duration = session.duration().unwrap_or_default();
+        println!(
+            "Stopped workout session with identifier {} after {}",
+            session.identifier,
+            format_duration(duration)
+        );
+        self.changes.push(Operation::Add {
+            session: session.clone(),
         });
-        println!("Successfully added new workout session");
+        let records = records::detect(&self.sessions, &session, records);
+        self.insert(session);
+        announce_records(&records);
+        Ok(())
     }
-    fn remove(&mut self, identifier: &str) -> Result<()> {
+    fn position(&self, identifier: &str) -> Result<usize> {
         let Some(index) = self
             .sessions
             .iter()
@@ -104,22 +381,66 @@ impl Storage {
                 "Identifier {identifier} was not found. Review identifiers with `list` command."
             ));
         };
-        self.sessions.remove(index);
+        Ok(index)
+    }
+    fn find(&self, identifier: &str) -> Result<&Session> {
+        Ok(&self.sessions[self.position(identifier)?])
+    }
+    fn edit(&mut self, updated: Session) -> Result<()> {
+        let identifier = updated.identifier.clone();
+        let index = self.position(&identifier)?;
+        let before = self.take(index);
+        self.changes.push(Operation::Edit {
+            before,
+            after: updated.clone(),
+        });
+        self.insert(updated);
+        println!("Successfully edited workout session with identifier {identifier}");
+        Ok(())
+    }
+    fn remove(&mut self, identifier: &str) -> Result<()> {
+        let index = self.position(identifier)?;
+        let session = self.take(index);
+        self.changes.push(Operation::Remove { session });
         println!("Successfully removed previous workout session with identifier {identifier}");
         Ok(())
     }
+    fn trainings_by_muscle(&self, query: &Query) -> HashMap<&str, Vec<(DateTime<Utc>, Intensity)>> {
+        let mut trainings: HashMap<&str, Vec<(DateTime<Utc>, Intensity)>> = HashMap::new();
+        for session in query.select(&self.sessions) {
+            for muscle in &session.muscles {
+                trainings
+                    .entry(muscle)
+                    .or_default()
+                    .push((session.finished_at().to_utc(), session.intensity()));
+            }
+        }
+        trainings
+    }
 }
 
-fn main() -> Result<()> {
-    let config = Config::setup()?;
-    let mut storage = Storage::read(&config)?;
+fn announce_records(records: &[records::Record]) {
+    for record in records {
+        println!("New personal record: {record}");
+    }
+}
 
-    let add_cmd = Command::new("add").about("Add a new workout session").arg(
-        Arg::new("description")
-            .help("A short description of the workout session")
-            .value_parser(NonEmptyStringValueParser::new())
-            .required(true),
-    );
+fn main() -> Result<()> {
+    let add_cmd = Command::new("add")
+        .about("Add a new workout session")
+        .arg(
+            Arg::new("description")
+                .help("A short description of the workout session")
+                .value_parser(NonEmptyStringValueParser::new())
+                .required(true),
+        )
+        .arg(muscles_arg())
+        .arg(exercises_arg())
+        .arg(tags_arg())
+        .arg(rpe_arg())
+        .arg(volume_arg())
+        .arg(at_arg())
+        .arg(ago_arg());
 
     let remove_cmd = Command::new("remove")
         .about("Remove a previous workout session")
@@ -130,37 +451,440 @@ fn main() -> Result<()> {
                 .required(true),
         );
 
+    let show_cmd = Command::new("show")
+        .about("Show a workout session in detail, including every set of its exercises")
+        .arg(
+            Arg::new("identifier")
+                .help("Identifier of the session to show")
+                .value_parser(NonEmptyStringValueParser::new())
+                .required(true),
+        );
+
     let list_cmd = Command::new("list")
-        .about("List all recent workout sessions in order")
+        .about("List recent workout sessions in order, optionally filtered")
+        .args(Query::filter_args())
+        .args(Query::presentation_args());
+
+    let start_cmd = Command::new("start")
+        .about("Start timing a new workout session")
+        .arg(
+            Arg::new("description")
+                .help("A short description of the workout session")
+                .value_parser(NonEmptyStringValueParser::new())
+                .required(true),
+        )
+        .arg(muscles_arg())
+        .arg(exercises_arg())
+        .arg(tags_arg());
+
+    let stop_cmd = Command::new("stop")
+        .about("Stop timing the workout session in progress")
+        .arg(exercises_arg())
+        .arg(rpe_arg())
+        .arg(volume_arg());
+
+    let edit_cmd = Command::new("edit")
+        .about("Edit a previous workout session, in $EDITOR unless any fields are given")
+        .arg(
+            Arg::new("identifier")
+                .help("Identifier of the session to edit")
+                .value_parser(NonEmptyStringValueParser::new())
+                .required(true),
+        )
+        .arg(
+            Arg::new("description")
+                .short('d')
+                .long("description")
+                .action(ArgAction::Set)
+                .value_parser(NonEmptyStringValueParser::new())
+                .help("New description of the workout session"),
+        )
+        .arg(muscles_arg())
+        .arg(exercises_arg())
+        .arg(tags_arg())
+        .arg(rpe_arg())
+        .arg(volume_arg())
+        .arg(at_arg())
+        .arg(ago_arg());
+
+    let migrate_backend_cmd = Command::new("migrate-backend")
+        .about("Move all workout sessions to another storage backend and switch to it")
+        .arg(
+            Arg::new("to")
+                .long("to")
+                .action(ArgAction::Set)
+                .value_parser(["json", "sqlite"])
+                .required(true)
+                .help("Backend to move workout sessions to"),
+        )
         .arg(
-            Arg::new("number")
+            Arg::new("force")
+                .long("force")
+                .action(ArgAction::SetTrue)
+                .help("Replace any workout sessions already stored in the target backend"),
+        );
+
+    let tag_arg = || {
+        Arg::new("tag")
+            .help("The tag")
+            .value_parser(NonEmptyStringValueParser::new())
+            .required(true)
+    };
+    let tag_identifier_arg = || {
+        Arg::new("identifier")
+            .help("Identifier of the session")
+            .value_parser(NonEmptyStringValueParser::new())
+            .required(true)
+    };
+    let tag_cmd = Command::new("tag")
+        .about("Add or remove a tag of a previous workout session")
+        .subcommand_required(true)
+        .subcommands([
+            Command::new("add")
+                .about("Tag a workout session")
+                .arg(tag_identifier_arg())
+                .arg(tag_arg()),
+            Command::new("remove")
+                .about("Remove a tag from a workout session")
+                .arg(tag_identifier_arg())
+                .arg(tag_arg()),
+        ]);
+
+    let tags_cmd = Command::new("tags")
+        .about("List every tag with the number of sessions using it")
+        .args(Query::filter_args());
+
+    let undo_cmd = Command::new("undo").about("Undo the last change to workout sessions");
+
+    let redo_cmd = Command::new("redo").about("Redo the last undone change to workout sessions");
+
+    let status_cmd = Command::new("status").about("Show the workout session in progress");
+
+    let recovery_cmd = Command::new("recovery")
+        .about("Show how recovered each muscle group is since it was last trained")
+        .args(Query::filter_args());
+
+    let load_cmd = Command::new("load")
+        .about("Show daily training load, fitness, fatigue and form")
+        .arg(
+            Arg::new("days")
                 .short('n')
-                .long("number")
+                .long("days")
                 .action(ArgAction::Set)
                 .value_parser(value_parser!(usize))
-                .help("Number of sessions to display"),
+                .help("Number of days to display"),
+        )
+        .arg(
+            Arg::new("date")
+                .long("date")
+                .action(ArgAction::Set)
+                .value_parser(value_parser!(NaiveDate))
+                .help("Last day to display, as YYYY-MM-DD (defaults to today)"),
+        )
+        .args(Query::filter_args());
+
+    let stats_cmd = Command::new("stats")
+        .about("Show training statistics over all or the selected workout sessions")
+        .args(Query::filter_args());
+
+    let calendar_cmd = Command::new("calendar")
+        .about("Show which days had workout sessions as a month grid or a year heatmap")
+        .arg(
+            Arg::new("month")
+                .long("month")
+                .action(ArgAction::Set)
+                .value_parser(parse_month)
+                .help("Month to show, as YYYY-MM (defaults to the current month)"),
+        )
+        .arg(
+            Arg::new("year")
+                .long("year")
+                .action(ArgAction::SetTrue)
+                .help("Show the whole year as a heatmap"),
+        )
+        .args(Query::filter_args());
+
+    let pr_cmd = Command::new("pr")
+        .about("List the history of personal records")
+        .arg(Arg::new("exercise").help("Only list records of this exercise"))
+        .args(Query::filter_args());
+
+    let config_cmd = Command::new("config")
+        .about("Inspect the configuration")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("path").about("Print the location where workout sessions are stored"),
         );
 
+    let exercise_name_arg = || {
+        Arg::new("name")
+            .help("Name of the exercise")
+            .value_parser(NonEmptyStringValueParser::new())
+            .required(true)
+    };
+    let exercise_cmd = Command::new("exercise")
+        .about("Manage the catalog of exercises and the muscle groups they train")
+        .subcommand_required(true)
+        .subcommands([
+            Command::new("list").about("List all exercises in the catalog"),
+            Command::new("add")
+                .about("Add an exercise to the catalog, or replace one with the same name")
+                .arg(exercise_name_arg())
+                .arg(
+                    Arg::new("primary")
+                        .long("primary")
+                        .action(ArgAction::Append)
+                        .value_delimiter(',')
+                        .value_parser(NonEmptyStringValueParser::new())
+                        .required(true)
+                        .help("Muscle groups mainly trained, separated by commas"),
+                )
+                .arg(
+                    Arg::new("secondary")
+                        .long("secondary")
+                        .action(ArgAction::Append)
+                        .value_delimiter(',')
+                        .value_parser(NonEmptyStringValueParser::new())
+                        .help("Muscle groups assisting, separated by commas"),
+                )
+                .arg(
+                    Arg::new("alias")
+                        .long("alias")
+                        .action(ArgAction::Append)
+                        .value_parser(NonEmptyStringValueParser::new())
+                        .help("Another name for the exercise"),
+                ),
+            Command::new("alias")
+                .about("Add another name for an exercise in the catalog")
+                .arg(
+                    Arg::new("alias")
+                        .help("The new name")
+                        .value_parser(NonEmptyStringValueParser::new())
+                        .required(true),
+                )
+                .arg(exercise_name_arg()),
+            Command::new("show")
+                .about("Show an exercise with the muscle groups it trains and when it was logged")
+                .arg(exercise_name_arg()),
+        ]);
+
+    let profile_name_arg = || {
+        Arg::new("name")
+            .help("Name of the profile")
+            .value_parser(NonEmptyStringValueParser::new())
+            .required(true)
+    };
+    let profile_cmd = Command::new("profile")
+        .about("Manage athlete profiles, each with their own sessions and settings")
+        .subcommand_required(true)
+        .subcommands([
+            Command::new("list").about("List all profiles, marking the default one"),
+            Command::new("create")
+                .about("Create a new profile")
+                .arg(profile_name_arg()),
+            Command::new("delete")
+                .about("Delete a profile along with all of its sessions and settings")
+                .arg(profile_name_arg()),
+            Command::new("rename")
+                .about("Rename a profile")
+                .arg(profile_name_arg())
+                .arg(
+                    Arg::new("new-name")
+                        .help("New name of the profile")
+                        .value_parser(NonEmptyStringValueParser::new())
+                        .required(true),
+                ),
+            Command::new("default")
+                .about("Show the default profile, or choose a new one")
+                .arg(profile_name_arg().required(false)),
+        ]);
+
     let root_cmd = command!()
-        .subcommands([add_cmd, remove_cmd, list_cmd])
+        .arg(
+            Arg::new("profile")
+                .short('p')
+                .long("profile")
+                .env("WR_PROFILE")
+                .global(true)
+                .action(ArgAction::Set)
+                .value_parser(NonEmptyStringValueParser::new())
+                .help("Profile to use instead of the default profile"),
+        )
+        .arg(
+            Arg::new("data-file")
+                .long("data-file")
+                .env("WR_DATA")
+                .global(true)
+                .action(ArgAction::Set)
+                .value_parser(value_parser!(PathBuf))
+                .help(
+                    "File to store workout sessions in, instead of the configured location \
+                     (WR_DATA only applies to the default profile)",
+                ),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .global(true)
+                .action(ArgAction::Set)
+                .value_parser(output::FORMATS)
+                .default_value("human")
+                .help("Output format of commands that read workout sessions"),
+        )
+        .subcommands([
+            add_cmd,
+            remove_cmd,
+            edit_cmd,
+            tag_cmd,
+            tags_cmd,
+            undo_cmd,
+            redo_cmd,
+            list_cmd,
+            show_cmd,
+            start_cmd,
+            stop_cmd,
+            status_cmd,
+            recovery_cmd,
+            load_cmd,
+            migrate_backend_cmd,
+            exercise_cmd,
+            pr_cmd,
+            stats_cmd,
+            calendar_cmd,
+            config_cmd,
+            profile_cmd,
+        ])
         .arg_required_else_help(true);
 
     let matches = root_cmd.get_matches();
+    let format = Format::from_name(
+        matches
+            .get_one::<String>("format")
+            .expect("format should have a default value"),
+    );
+
+    if let Some(("profile", submatches)) = matches.subcommand() {
+        return profile_command(submatches, format);
+    }
+    let mut config = Config::setup(&matches)?;
+    if let Some(("config", submatches)) = matches.subcommand() {
+        return config_command(submatches, &config, format);
+    }
+    let lock_timeout = Duration::from_secs(config.settings.lock.timeout_seconds);
+    let _lock = persist::lock(&config.lock_path, lock_timeout)?;
+    let backend = config.backend(config.settings.backend);
+    let mut storage = backend.read()?;
+    let mut journal = Journal::read(&config.journal_path)?;
+
     match matches.subcommand() {
-        Some(("add", submatches)) => add(submatches, &mut storage),
+        Some(("add", submatches)) => add(submatches, &config, &mut storage)?,
         Some(("remove", submatches)) => remove(submatches, &mut storage)?,
-        Some(("list", submatches)) => list(submatches, &storage),
+        Some(("edit", submatches)) => edit(submatches, &config, &mut storage)?,
+        Some(("tag", submatches)) => tag(submatches, &mut storage)?,
+        Some(("tags", submatches)) => tags_command(submatches, &storage, format)?,
+        Some(("undo", _)) => journal.undo(&mut storage)?,
+        Some(("redo", _)) => journal.redo(&mut storage)?,
+        Some(("list", submatches)) => list(submatches, &config, &storage, format)?,
+        Some(("show", submatches)) => show(submatches, &storage, format)?,
+        Some(("start", submatches)) => start(submatches, &config, &mut storage)?,
+        Some(("stop", submatches)) => stop(submatches, &config, &mut storage)?,
+        Some(("status", _)) => status(&storage, format)?,
+        Some(("recovery", submatches)) => recovery(submatches, &config, &storage, format)?,
+        Some(("load", submatches)) => load(submatches, &config, &storage, format)?,
+        Some(("exercise", submatches)) => exercise_command(submatches, &config, &storage, format)?,
+        Some(("calendar", submatches)) => calendar(submatches, &storage, format)?,
+        Some(("stats", submatches)) => stats(submatches, &config, &storage, format)?,
+        Some(("pr", submatches)) => personal_records(submatches, &config, &storage, format)?,
+        Some(("migrate-backend", submatches)) => {
+            migrate_backend(submatches, &mut config, &storage)?
+        }
         _ => unreachable!("should exhaustively check every parsed subcommand"),
     };
 
-    storage.save(&config)?;
+    journal.record(
+        mem::take(&mut storage.changes),
+        config.settings.journal.history_limit,
+    );
+    if storage.dirty {
+        backend.save(&storage)?;
+    }
+    if journal.dirty {
+        journal.save(&config.journal_path)?;
+    }
     Ok(())
 }
 
+fn muscles_arg() -> Arg {
+    Arg::new("muscles")
+        .short('m')
+        .long("muscles")
+        .action(ArgAction::Append)
+        .value_delimiter(',')
+        .value_parser(NonEmptyStringValueParser::new())
+        .help("Muscle groups trained, separated by commas (e.g. chest,triceps)")
+}
+
+fn exercises_arg() -> Arg {
+    Arg::new("exercises")
+        .short('e')
+        .long("exercise")
+        .action(ArgAction::Append)
+        .value_parser(exercise::parse)
+        .help("An exercise with its sets (e.g. \"bench 3x5@100kg\", \"dips 3x10\", \"squat 5@100kg rpe7, 3x3@120kg\")")
+}
+
+fn tags_arg() -> Arg {
+    Arg::new("tags")
+        .short('t')
+        .long("tag")
+        .action(ArgAction::Append)
+        .value_parser(NonEmptyStringValueParser::new())
+        .help("A tag to categorize the session by (e.g. cardio), repeated for several")
+}
+
+fn rpe_arg() -> Arg {
+    Arg::new("rpe")
+        .long("rpe")
+        .action(ArgAction::Set)
+        .value_parser(value_parser!(u8).range(1..=10))
+        .help("Rate of perceived exertion for the whole session, from 1 to 10")
+}
+
+fn volume_arg() -> Arg {
+    Arg::new("volume")
+        .long("volume")
+        .action(ArgAction::Set)
+        .value_parser(parse_volume)
+        .help("Total volume of the session (e.g. sets x reps x load)")
+}
+
+fn at_arg() -> Arg {
+    Arg::new("at")
+        .long("at")
+        .action(ArgAction::Set)
+        .value_parser(NonEmptyStringValueParser::new())
+        .conflicts_with("ago")
+        .help("When the session took place (e.g. 2026-10-18T18:00:00+02:00, \"2026-10-18 18:00\", \"yesterday 18:00\", \"3h ago\")")
+}
+
+fn ago_arg() -> Arg {
+    Arg::new("ago")
+        .long("ago")
+        .action(ArgAction::Set)
+        .value_parser(NonEmptyStringValueParser::new())
+        .help("How long ago the session took place (e.g. 3h, 1d 2h)")
+}
+
 fn new_id(storage: &Storage) -> String {
     loop {
         let new_id = generate_one_id();
-        if !storage.sessions.iter().any(|s| s.identifier == new_id) {
+        let active = storage.active.iter();
+        if !storage
+            .sessions
+            .iter()
+            .chain(active)
+            .any(|s| s.identifier == new_id)
+        {
             break new_id;
         }
     }
@@ -174,11 +898,109 @@ fn generate_one_id() -> String {
         .collect()
 }
 
-fn add(submatches: &ArgMatches, storage: &mut Storage) {
+fn add(submatches: &ArgMatches, config: &Config, storage: &mut Storage) -> Result<()> {
     let description = submatches
         .get_one::<String>("description")
         .expect("description should be parsed to be a valid string");
-    storage.add(description);
+    let timestamp = session_timestamp(submatches)?.unwrap_or_else(timestamp::now);
+    let intensity = intensity(submatches);
+    let mut session = Session {
+        muscles: muscles(submatches),
+        rpe: intensity.rpe,
+        volume: intensity.volume,
+        exercises: exercises(submatches),
+        tags: tags(submatches),
+        ..Session::new(description, timestamp)
+    };
+    attribute_muscles(config, &mut session)?;
+    storage.add(session, &config.settings.records);
+    warn_workload(config, storage);
+    Ok(())
+}
+
+fn muscles(submatches: &ArgMatches) -> Vec<String> {
+    submatches
+        .get_many::<String>("muscles")
+        .map(normalize_labels)
+        .unwrap_or_default()
+}
+
+fn tags(submatches: &ArgMatches) -> Vec<String> {
+    submatches
+        .get_many::<String>("tags")
+        .map(normalize_labels)
+        .unwrap_or_default()
+}
+
+fn exercises(submatches: &ArgMatches) -> Vec<Exercise> {
+    submatches
+        .get_many::<Exercise>("exercises")
+        .map(|exercises| exercises.cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Names the session's exercises as in the catalog and adds the muscle groups they train
+fn attribute_muscles(config: &Config, session: &mut Session) -> Result<()> {
+    if session.exercises.is_empty() {
+        return Ok(());
+    }
+    let catalog = Catalog::read(&config.catalog_path)?;
+    for muscle in catalog.attribute(&mut session.exercises) {
+        if !session.muscles.contains(&muscle) {
+            session.muscles.push(muscle);
+        }
+    }
+    Ok(())
+}
+
+fn intensity(submatches: &ArgMatches) -> Intensity {
+    Intensity {
+        rpe: submatches.get_one::<u8>("rpe").copied(),
+        volume: submatches.get_one::<f64>("volume").copied(),
+    }
+}
+
+fn session_timestamp(submatches: &ArgMatches) -> Result<Option<Timestamp>> {
+    let now = Local::now();
+    let timestamp = if let Some(at) = submatches.get_one::<String>("at") {
+        timestamp::parse_at(at, now)?
+    } else if let Some(ago) = submatches.get_one::<String>("ago") {
+        timestamp::ago(ago, now)?
+    } else {
+        return Ok(None);
+    };
+    check_not_future(timestamp)?;
+    Ok(Some(timestamp))
+}
+
+fn check_not_future(timestamp: Timestamp) -> Result<()> {
+    if timestamp > Local::now() {
+        return Err(anyhow!(
+            "The session time {} is in the future",
+            timestamp.format("%Y-%m-%d %H:%M")
+        ));
+    }
+    Ok(())
+}
+
+fn parse_volume(value: &str) -> Result<f64> {
+    let volume: f64 = value.parse().context("volume should be a number")?;
+    if !volume.is_finite() || volume < 0.0 {
+        return Err(anyhow!("volume should be a non-negative number"));
+    }
+    Ok(volume)
+}
+
+/// Lowercases muscle groups or tags and drops empty and repeated ones
+fn normalize_labels<'a>(values: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::new();
+    for value in values {
+        let label = value.trim().to_lowercase();
+        if !label.is_empty() && !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+    labels
 }
 
 fn remove(submatches: &ArgMatches, storage: &mut Storage) -> Result<()> {
@@ -189,17 +1011,288 @@ fn remove(submatches: &ArgMatches, storage: &mut Storage) -> Result<()> {
     Ok(())
 }
 
-fn list(submatches: &ArgMatches, storage: &Storage) {
-    if let Some(num) = submatches.get_one::<usize>("number") {
-        output_list(*num, storage);
+fn start(submatches: &ArgMatches, config: &Config, storage: &mut Storage) -> Result<()> {
+    let description = submatches
+        .get_one::<String>("description")
+        .expect("description should be parsed to be a valid string");
+    let mut session = Session {
+        muscles: muscles(submatches),
+        exercises: exercises(submatches),
+        tags: tags(submatches),
+        ..Session::new(description, timestamp::now())
+    };
+    attribute_muscles(config, &mut session)?;
+    storage.start(session)
+}
+
+fn stop(submatches: &ArgMatches, config: &Config, storage: &mut Storage) -> Result<()> {
+    let mut exercises = exercises(submatches);
+    let muscles = if exercises.is_empty() {
+        Vec::new()
+    } else {
+        Catalog::read(&config.catalog_path)?.attribute(&mut exercises)
+    };
+    storage.stop(
+        intensity(submatches),
+        exercises,
+        muscles,
+        &config.settings.records,
+    )?;
+    warn_workload(config, storage);
+    Ok(())
+}
+
+fn status(storage: &Storage, format: Format) -> Result<()> {
+    if format != Format::Human {
+        return session_table(storage.active.iter()).print(format);
+    }
+    let Some(session) = &storage.active else {
+        println!("No workout session in progress.");
+        return Ok(());
+    };
+    let started = session.timestamp;
+    println!("{:>15} {}", "[Identifier]", session.identifier);
+    println!("{:>15} {}", "[Description]", session.description);
+    if !session.muscles.is_empty() {
+        println!("{:>15} {}", "[Muscles]", session.muscles.join(", "));
+    }
+    if !session.tags.is_empty() {
+        println!("{:>15} {}", "[Tags]", session.tags.join(", "));
+    }
+    println!("{:>15} {}", "[Started]", started.format("%Y-%m-%d %H:%M"));
+    println!(
+        "{:>15} {}",
+        "[Running]",
+        format_duration(time_elapsed(session.timestamp))
+    );
+    Ok(())
+}
+
+fn edit(submatches: &ArgMatches, config: &Config, storage: &mut Storage) -> Result<()> {
+    let identifier = submatches
+        .get_one::<String>("identifier")
+        .expect("identifier should be parsed to be a valid string");
+    let original = storage.find(identifier)?;
+    let field_ids = [
+        "description",
+        "muscles",
+        "rpe",
+        "volume",
+        "exercises",
+        "tags",
+        "at",
+        "ago",
+    ];
+    let updated = if field_ids.iter().any(|id| submatches.contains_id(id)) {
+        let mut updated = original.clone();
+        if let Some(description) = submatches.get_one::<String>("description") {
+            updated.description = description.to_owned();
+        }
+        if submatches.contains_id("muscles") {
+            updated.muscles = muscles(submatches);
+        }
+        if submatches.contains_id("tags") {
+            updated.tags = tags(submatches);
+        }
+        if submatches.contains_id("exercises") {
+            updated.exercises = exercises(submatches);
+            attribute_muscles(config, &mut updated)?;
+        }
+        let intensity = intensity(submatches);
+        updated.rpe = intensity.rpe.or(updated.rpe);
+        updated.volume = intensity.volume.or(updated.volume);
+        if let Some(timestamp) = session_timestamp(submatches)? {
+            // Timed sessions keep their duration when moved
+            if let Some(duration) = updated.duration() {
+                updated.end = Some(timestamp + duration);
+            }
+            updated.timestamp = timestamp;
+        }
+        updated
     } else {
-        output_list(10, storage);
+        let mut updated: Session = editor::edit_json(original, identifier)?;
+        if updated.identifier != original.identifier {
+            return Err(anyhow!("The identifier of a session cannot be edited"));
+        }
+        if updated.timestamp != original.timestamp {
+            check_not_future(updated.timestamp)?;
+        }
+        updated.muscles = normalize_labels(updated.muscles.iter());
+        updated.tags = normalize_labels(updated.tags.iter());
+        for exercise in &mut updated.exercises {
+            exercise.name = exercise::normalize_name(&exercise.name);
+        }
+        updated
+    };
+    updated.validate()?;
+    storage.edit(updated)
+}
+
+fn tag(submatches: &ArgMatches, storage: &mut Storage) -> Result<()> {
+    let (action, submatches) = submatches
+        .subcommand()
+        .expect("tag subcommand should be required");
+    let identifier = submatches
+        .get_one::<String>("identifier")
+        .expect("identifier should be parsed to be a valid string");
+    let tag = normalize_labels(submatches.get_one::<String>("tag").into_iter())
+        .pop()
+        .ok_or_else(|| anyhow!("Tag must not be empty"))?;
+    let mut updated = storage.find(identifier)?.clone();
+    let tagged = updated.tags.contains(&tag);
+    match action {
+        "add" if tagged => {
+            return Err(anyhow!(
+                "Workout session {identifier} is already tagged {tag}"
+            ));
+        }
+        "add" => updated.tags.push(tag),
+        "remove" if !tagged => {
+            return Err(anyhow!("Workout session {identifier} is not tagged {tag}"));
+        }
+        "remove" => updated.tags.retain(|t| t != &tag),
+        _ => unreachable!("should exhaustively check every parsed tag subcommand"),
+    }
+    storage.edit(updated)
+}
+
+fn tags_command(submatches: &ArgMatches, storage: &Storage, format: Format) -> Result<()> {
+    let query = Query::from_matches(submatches)?;
+    let mut usage: Vec<(&str, usize, Timestamp)> = Vec::new();
+    for session in query.select(&storage.sessions) {
+        for tag in &session.tags {
+            match usage.iter_mut().find(|(t, ..)| t == tag) {
+                Some((_, count, last)) => {
+                    *count += 1;
+                    *last = cmp::max(*last, session.timestamp);
+                }
+                None => usage.push((tag, 1, session.timestamp)),
+            }
+        }
+    }
+    usage.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    if format != Format::Human {
+        return tags_table(&usage).print(format);
+    }
+    if usage.is_empty() {
+        println!("No tags recorded yet. Add them with `add --tag` or `tag add`.");
+        return Ok(());
     }
+    let width = usage.iter().map(|(t, ..)| t.len() + 2).max().unwrap_or(0);
+    for (tag, count, _) in usage {
+        println!("{:>width$} {count}", format!("[{tag}]"));
+    }
+    Ok(())
 }
 
-fn output_list(count_requested: usize, storage: &Storage) {
-    let mut count = cmp::min(count_requested, storage.sessions.len());
-    let selected_sessions = storage.sessions.iter().rev().take(count).rev();
+fn tags_table(usage: &[(&str, usize, Timestamp)]) -> Table {
+    let mut table = Table::new(&["tag", "sessions", "last_used"]);
+    for (tag, count, last) in usage {
+        table.push(vec![json!(tag), json!(count), json!(last.to_rfc3339())]);
+    }
+    table
+}
+
+fn show(submatches: &ArgMatches, storage: &Storage, format: Format) -> Result<()> {
+    let identifier = submatches
+        .get_one::<String>("identifier")
+        .expect("identifier should be parsed to be a valid string");
+    let session = storage.find(identifier)?;
+    if format != Format::Human {
+        return session_table([session]).print(format);
+    }
+    print!("{session}");
+    println!(
+        "{:>15} {}",
+        "[Started]",
+        session.timestamp.format("%Y-%m-%d %H:%M %:z")
+    );
+    for exercise in &session.exercises {
+        println!();
+        println!("{:>15} {}", "[Exercise]", exercise.name);
+        for (index, set) in exercise.sets.iter().enumerate() {
+            println!("{:>15} {set}", format!("[Set {}]", index + 1));
+        }
+        println!("{:>15} {:.1} kg", "[Volume]", exercise.volume());
+    }
+    if let Some(volume) = session.exercise_volume() {
+        println!();
+        println!("{:>15} {volume:.1} kg", "[Total Volume]");
+    }
+    Ok(())
+}
+
+fn list(submatches: &ArgMatches, config: &Config, storage: &Storage, format: Format) -> Result<()> {
+    let query = Query::from_matches(submatches)?.presented(submatches, Some(10));
+    let selected_sessions = query.select(&storage.sessions);
+    if format != Format::Human {
+        return session_table(selected_sessions).print(format);
+    }
+    output_list(&selected_sessions);
+    warn_workload(config, storage);
+    Ok(())
+}
+
+const SESSION_COLUMNS: &[&str] = &[
+    "identifier",
+    "description",
+    "timestamp",
+    "end",
+    "duration_minutes",
+    "muscles",
+    "tags",
+    "rpe",
+    "volume",
+    "exercise_volume",
+    "exercises",
+];
+
+fn session_table<'a>(sessions: impl IntoIterator<Item = &'a Session>) -> Table {
+    let mut table = Table::new(SESSION_COLUMNS);
+    for session in sessions {
+        table.push(vec![
+            json!(session.identifier),
+            json!(session.description),
+            json!(session.timestamp.to_rfc3339()),
+            json!(session.end.map(|end| end.to_rfc3339())),
+            json!(session.duration().map(|duration| duration.num_minutes())),
+            json!(session.muscles),
+            json!(session.tags),
+            json!(session.rpe),
+            json!(session.volume),
+            json!(session.exercise_volume()),
+            json!(session.exercises),
+        ]);
+    }
+    table
+}
+
+fn warn_workload(config: &Config, storage: &Storage) {
+    let model = LoadModel::new(&config.settings.load);
+    let today = Local::now().date_naive();
+    let acwr = &config.settings.acwr;
+    if let Some(ratio) = acwr.ratio(&daily_loads(&model, &storage.sessions), today)
+        && acwr.is_dangerous(ratio)
+    {
+        println!(
+            "\nWarning: acute:chronic workload ratio is {ratio:.2}, above the danger threshold of {:.2}",
+            acwr.danger_threshold
+        );
+    }
+}
+
+fn daily_loads<'a>(
+    model: &LoadModel,
+    sessions: impl IntoIterator<Item = &'a Session>,
+) -> Vec<(NaiveDate, f64)> {
+    sessions
+        .into_iter()
+        .map(|s| (s.timestamp.date_naive(), model.session_load(s.rpe)))
+        .collect()
+}
+
+fn output_list(selected_sessions: &[&Session]) {
+    let mut count = selected_sessions.len();
     for session in selected_sessions {
         if count != 1 {
             println!("{session}")
@@ -209,3 +1302,748 @@ fn output_list(count_requested: usize, storage: &Storage) {
         count -= 1;
     }
 }
+
+fn recovery(
+    submatches: &ArgMatches,
+    config: &Config,
+    storage: &Storage,
+    format: Format,
+) -> Result<()> {
+    let query = Query::from_matches(submatches)?;
+    let model = RecoveryModel::new(&config.settings.recovery);
+    let now = Utc::now();
+    let mut readiness: Vec<_> = storage
+        .trainings_by_muscle(&query)
+        .into_iter()
+        .filter_map(|(muscle, trainings)| {
+            model
+                .assess_all(muscle, &trainings, now)
+                .map(|readiness| (muscle, readiness))
+        })
+        .collect();
+    readiness.sort_by(|a, b| a.1.elapsed.cmp(&b.1.elapsed).then(a.0.cmp(b.0)));
+    if format != Format::Human {
+        let mut table = Table::new(&[
+            "muscle",
+            "status",
+            "percent_recovered",
+            "elapsed_hours",
+            "window_hours",
+            "last_trained",
+        ]);
+        for (muscle, readiness) in readiness {
+            let last_trained = (now - readiness.elapsed).with_timezone(&Local);
+            table.push(vec![
+                json!(muscle),
+                json!(readiness.status.to_string().to_lowercase()),
+                json!(readiness.percent_recovered),
+                json!(hours(readiness.elapsed)),
+                json!(hours(readiness.window)),
+                json!(last_trained.to_rfc3339()),
+            ]);
+        }
+        return table.print(format);
+    }
+    if readiness.is_empty() {
+        println!("No muscle groups recorded yet. Add them with `add --muscles`.");
+        return Ok(());
+    }
+    let width = readiness
+        .iter()
+        .map(|(m, _)| m.len() + 2)
+        .max()
+        .unwrap_or(0);
+    for (muscle, readiness) in readiness {
+        println!(
+            "{:>width$} {:<10} {:>3.0}% | {}",
+            format!("[{muscle}]"),
+            readiness.status,
+            readiness.percent_recovered.floor(),
+            format_elapsed(readiness.elapsed)
+        );
+    }
+    Ok(())
+}
+
+fn hours(duration: TimeDelta) -> f64 {
+    duration.num_seconds() as f64 / 3600.0
+}
+
+fn load(submatches: &ArgMatches, config: &Config, storage: &Storage, format: Format) -> Result<()> {
+    let days = *submatches.get_one::<usize>("days").unwrap_or(&14);
+    let until = submatches
+        .get_one::<NaiveDate>("date")
+        .copied()
+        .unwrap_or_else(|| Local::now().date_naive());
+    let query = Query::from_matches(submatches)?;
+    let model = LoadModel::new(&config.settings.load);
+    let loads = daily_loads(&model, query.select(&storage.sessions));
+    let daily = model.daily(&loads, until);
+    let shown = &daily[daily.len().saturating_sub(days)..];
+    if format != Format::Human {
+        return load_table(shown, &loads, &config.settings.acwr).print(format);
+    }
+    if daily.is_empty() {
+        println!("No workout sessions recorded up to {until}.");
+        return Ok(());
+    }
+    println!(
+        "{:>10} {:>8} {:>8} {:>8} {:>8} {:>6}",
+        "Date", "Load", "Fitness", "Fatigue", "Form", "ACWR"
+    );
+    for day in shown {
+        let acwr = match config.settings.acwr.ratio(&loads, day.date) {
+            Some(ratio) => format!("{ratio:.2}"),
+            None => "-".to_owned(),
+        };
+        println!(
+            "{:>10} {:>8.1} {:>8.2} {:>8.2} {:>8.2} {:>6}",
+            day.date, day.load, day.fitness, day.fatigue, day.form, acwr
+        );
+    }
+    Ok(())
+}
+
+fn load_table(days: &[load::DailyLoad], loads: &[(NaiveDate, f64)], acwr: &AcwrSettings) -> Table {
+    let mut table = Table::new(&["date", "load", "fitness", "fatigue", "form", "acwr"]);
+    for day in days {
+        table.push(vec![
+            json!(day.date.to_string()),
+            json!(day.load),
+            json!(day.fitness),
+            json!(day.fatigue),
+            json!(day.form),
+            json!(acwr.ratio(loads, day.date)),
+        ]);
+    }
+    table
+}
+
+fn stats(
+    submatches: &ArgMatches,
+    config: &Config,
+    storage: &Storage,
+    format: Format,
+) -> Result<()> {
+    let query = Query::from_matches(submatches)?;
+    let mut selected = query.select(&storage.sessions);
+    selected.sort_by_key(|s| s.timestamp);
+    let catalog = Catalog::read(&config.catalog_path)?;
+    let stats = analytics::analyze(&selected, Local::now().date_naive(), &catalog);
+    if format != Format::Human {
+        return stats_table(&stats).print(format);
+    }
+    let (Some(first), Some(last)) = (stats.first, stats.last) else {
+        println!("No workout sessions selected.");
+        return Ok(());
+    };
+    let date = |timestamp: Timestamp| timestamp.format("%Y-%m-%d");
+    println!(
+        "{:>17} {} ({} to {})",
+        "[Sessions]",
+        stats.sessions,
+        date(first),
+        date(last)
+    );
+    if let Some(average_gap) = stats.average_gap {
+        println!("{:>17} {}", "[Average Gap]", format_elapsed(average_gap));
+    }
+    if let Some(gap) = stats.longest_gap {
+        println!(
+            "{:>17} {} ({} to {})",
+            "[Longest Gap]",
+            format_elapsed(gap.length),
+            date(gap.from),
+            date(gap.to)
+        );
+    }
+    println!("{:>17} {} days", "[Current Streak]", stats.current_streak);
+    println!("{:>17} {} days", "[Longest Streak]", stats.longest_streak);
+    if let Some((weekday, count)) = stats.busiest_weekday {
+        println!("{:>17} {weekday} ({count} sessions)", "[Busiest Weekday]");
+    }
+    if let Some((hour, count)) = stats.busiest_hour {
+        println!("{:>17} {hour:02}:00 ({count} sessions)", "[Busiest Hour]");
+    }
+    println!();
+    println!("{:>17}", "[Per Month]");
+    for (month, count) in &stats.per_month {
+        println!("{month:>17} {count}");
+    }
+    println!();
+    println!("{:>17}", "[Per Week]");
+    for (week, count) in &stats.per_week {
+        println!("{week:>17} {count}");
+    }
+    if !stats.volume_by_muscle.is_empty() {
+        println!();
+        println!("{:>17}", "[Volume]");
+        for (muscle, volume) in &stats.volume_by_muscle {
+            println!("{muscle:>17} {volume:.1}");
+        }
+    }
+    Ok(())
+}
+
+/// One row per figure keeps every report in the same columns
+fn stats_table(stats: &analytics::Stats) -> Table {
+    let mut table = Table::new(&["statistic", "key", "value"]);
+    let mut push = |statistic: &str, key: &str, value: serde_json::Value| {
+        table.push(vec![json!(statistic), json!(key), value]);
+    };
+    push("sessions", "", json!(stats.sessions));
+    push("first", "", json!(stats.first.map(|t| t.to_rfc3339())));
+    push("last", "", json!(stats.last.map(|t| t.to_rfc3339())));
+    for (week, count) in &stats.per_week {
+        push("sessions_per_week", week, json!(count));
+    }
+    for (month, count) in &stats.per_month {
+        push("sessions_per_month", month, json!(count));
+    }
+    push("average_gap_hours", "", json!(stats.average_gap.map(hours)));
+    push(
+        "longest_gap_hours",
+        "",
+        json!(stats.longest_gap.map(|gap| hours(gap.length))),
+    );
+    push("current_streak_days", "", json!(stats.current_streak));
+    push("longest_streak_days", "", json!(stats.longest_streak));
+    if let Some((weekday, count)) = stats.busiest_weekday {
+        push("busiest_weekday", &weekday.to_string(), json!(count));
+    }
+    if let Some((hour, count)) = stats.busiest_hour {
+        push("busiest_hour", &hour.to_string(), json!(count));
+    }
+    for (muscle, volume) in &stats.volume_by_muscle {
+        push("volume_by_muscle", muscle, json!(volume));
+    }
+    table
+}
+
+fn parse_month(value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("{}-01", value.trim()), "%Y-%m-%d")
+        .context("month should be given as YYYY-MM")
+}
+
+fn calendar(submatches: &ArgMatches, storage: &Storage, format: Format) -> Result<()> {
+    let query = Query::from_matches(submatches)?;
+    let today = Local::now().date_naive();
+    let month = submatches.get_one::<NaiveDate>("month").copied();
+    let year = submatches.get_flag("year");
+    let (first, last) = match (month, year) {
+        (month, true) => {
+            let year = month.unwrap_or(today).year();
+            (
+                NaiveDate::from_ymd_opt(year, 1, 1),
+                NaiveDate::from_ymd_opt(year, 12, 31),
+            )
+        }
+        (month, false) => {
+            let first = month.unwrap_or(today).with_day(1);
+            let last = first
+                .and_then(|first| first.checked_add_months(Months::new(1)))
+                .and_then(|next| next.pred_opt());
+            (first, last)
+        }
+    };
+    let (Some(first), Some(last)) = (first, last) else {
+        return Err(anyhow!("The requested dates are out of range"));
+    };
+    let mut counts = calendar::Counts::new();
+    for session in query.select(&storage.sessions) {
+        let date = session.timestamp.date_naive();
+        if (first..=last).contains(&date) {
+            *counts.entry(date).or_default() += 1;
+        }
+    }
+    if format != Format::Human {
+        let mut table = Table::new(&["date", "sessions"]);
+        for day in first.iter_days().take_while(|day| *day <= last) {
+            let count = counts.get(&day).copied().unwrap_or_default();
+            table.push(vec![json!(day.to_string()), json!(count)]);
+        }
+        return table.print(format);
+    }
+    let colour = io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    if year {
+        print!("{}", calendar::year(first.year(), &counts, today, colour));
+    } else {
+        print!("{}", calendar::month(first, &counts, today, colour));
+    }
+    Ok(())
+}
+
+fn personal_records(
+    submatches: &ArgMatches,
+    config: &Config,
+    storage: &Storage,
+    format: Format,
+) -> Result<()> {
+    // Records of exercises logged with typos count towards the exercise they were taken for
+    let catalog = Catalog::read(&config.catalog_path)?;
+    let counts_as = |name: &str| match catalog.counts_as(name) {
+        Some(entry) => entry.name.clone(),
+        None => exercise::normalize_name(name),
+    };
+    let exercise = submatches
+        .get_one::<String>("exercise")
+        .map(|name| counts_as(name));
+    // Records are set against the whole history, and only then kept if their session matches
+    let query = Query::from_matches(submatches)?;
+    let selected: Vec<&str> = query
+        .select(&storage.sessions)
+        .into_iter()
+        .map(|s| s.identifier.as_str())
+        .collect();
+    let mut history: Vec<_> = records::history(&storage.sessions, &config.settings.records)
+        .into_iter()
+        .filter(|record| {
+            selected.contains(&record.identifier.as_str())
+                && exercise
+                    .as_ref()
+                    .is_none_or(|name| counts_as(&record.exercise) == *name)
+        })
+        .collect();
+    // Stable sort keeps each exercise's records in chronological order
+    history.sort_by(|a, b| a.exercise.cmp(&b.exercise));
+    if format != Format::Human {
+        return records_table(&history).print(format);
+    }
+    if history.is_empty() {
+        match exercise {
+            Some(name) => println!("No personal records for {name} yet."),
+            None => println!("No personal records yet. Log exercises with `add --exercise`."),
+        }
+        return Ok(());
+    }
+    let mut previous_exercise = None;
+    for record in &history {
+        if previous_exercise != Some(&record.exercise) {
+            if previous_exercise.is_some() {
+                println!();
+            }
+            println!("[{}]", record.exercise);
+            previous_exercise = Some(&record.exercise);
+        }
+        println!(
+            "  {} {} {:<15} {:>8.1} kg",
+            record.timestamp.format("%Y-%m-%d"),
+            record.identifier,
+            record.kind.to_string(),
+            record.value
+        );
+    }
+    Ok(())
+}
+
+fn records_table(history: &[records::Record]) -> Table {
+    let mut table = Table::new(&[
+        "exercise",
+        "record",
+        "reps",
+        "value_kg",
+        "previous_kg",
+        "identifier",
+        "timestamp",
+    ]);
+    for record in history {
+        table.push(vec![
+            json!(record.exercise),
+            json!(record.kind.name()),
+            json!(record.kind.reps()),
+            json!(record.value),
+            json!(record.previous),
+            json!(record.identifier),
+            json!(record.timestamp.to_rfc3339()),
+        ]);
+    }
+    table
+}
+
+fn migrate_backend(submatches: &ArgMatches, config: &mut Config, storage: &Storage) -> Result<()> {
+    let to = match submatches.get_one::<String>("to").map(String::as_str) {
+        Some("sqlite") => BackendKind::Sqlite,
+        _ => BackendKind::Json,
+    };
+    let from = config.settings.backend;
+    if from == to {
+        return Err(anyhow!(
+            "Workout sessions are already stored in the {to} backend"
+        ));
+    }
+    let target = config.backend(to);
+    if !submatches.get_flag("force") && !target.is_empty()? {
+        return Err(anyhow!(
+            "The {to} backend already contains workout sessions. Use --force to replace them."
+        ));
+    }
+    target.save(storage)?;
+    config.settings.backend = to;
+    config.save_settings()?;
+    // Leaves the source empty, so that moving back later doesn't need --force
+    config.backend(from).set_aside()?;
+    println!(
+        "Moved {} workout sessions from the {from} backend to the {to} backend",
+        storage.sessions.len()
+    );
+    Ok(())
+}
+
+const EXERCISE_COLUMNS: &[&str] = &["name", "primary", "secondary", "aliases", "custom"];
+
+fn exercise_row(entry: &catalog::Entry) -> Vec<serde_json::Value> {
+    vec![
+        json!(entry.name),
+        json!(entry.primary),
+        json!(entry.secondary),
+        json!(entry.aliases),
+        json!(entry.custom),
+    ]
+}
+
+fn exercise_command(
+    submatches: &ArgMatches,
+    config: &Config,
+    storage: &Storage,
+    format: Format,
+) -> Result<()> {
+    let mut catalog = Catalog::read(&config.catalog_path)?;
+    let name = |submatches: &ArgMatches| {
+        submatches
+            .get_one::<String>("name")
+            .map(|name| exercise::normalize_name(name))
+            .expect("name should be parsed to be a valid string")
+    };
+    match submatches.subcommand() {
+        Some(("list", _)) if format != Format::Human => {
+            let mut table = Table::new(EXERCISE_COLUMNS);
+            for entry in catalog.entries() {
+                table.push(exercise_row(entry));
+            }
+            table.print(format)?;
+        }
+        Some(("list", _)) => {
+            let width = catalog
+                .entries()
+                .iter()
+                .map(|e| e.name.len() + 2)
+                .max()
+                .unwrap_or(0);
+            for entry in catalog.entries() {
+                let mut muscles = entry.primary.join(", ");
+                if !entry.secondary.is_empty() {
+                    muscles.push_str(&format!(" ({})", entry.secondary.join(", ")));
+                }
+                let marker = if entry.custom { " *" } else { "" };
+                println!("{:>width$} {muscles}{marker}", format!("[{}]", entry.name));
+            }
+        }
+        Some(("add", submatches)) => {
+            let list = |id: &str| {
+                submatches
+                    .get_many::<String>(id)
+                    .map(normalize_labels)
+                    .unwrap_or_default()
+            };
+            let aliases = submatches
+                .get_many::<String>("alias")
+                .map(|aliases| aliases.map(|a| exercise::normalize_name(a)).collect())
+                .unwrap_or_default();
+            catalog.add(catalog::Entry {
+                name: name(submatches),
+                primary: list("primary"),
+                secondary: list("secondary"),
+                aliases,
+                custom: true,
+            })?;
+        }
+        Some(("alias", submatches)) => {
+            let alias = submatches
+                .get_one::<String>("alias")
+                .expect("alias should be parsed to be a valid string");
+            catalog.alias(alias, &name(submatches))?;
+        }
+        Some(("show", submatches)) => {
+            let name = name(submatches);
+            let Some(entry) = catalog.counts_as(&name) else {
+                return Err(anyhow!(
+                    "Exercise {name} is not in the catalog. Review exercises with `exercise list`."
+                ));
+            };
+            let logged: Vec<&Session> = storage
+                .sessions
+                .iter()
+                .filter(|s| {
+                    s.exercises.iter().any(|e| {
+                        catalog
+                            .counts_as(&e.name)
+                            .is_some_and(|logged| logged.name == entry.name)
+                    })
+                })
+                .collect();
+            if format != Format::Human {
+                let mut table =
+                    Table::new(&[EXERCISE_COLUMNS, &["sessions", "last_logged"]].concat());
+                let mut row = exercise_row(entry);
+                row.push(json!(logged.len()));
+                row.push(json!(logged.last().map(|s| s.timestamp.to_rfc3339())));
+                table.push(row);
+                return table.print(format);
+            }
+            println!("{:>15} {}", "[Name]", entry.name);
+            println!("{:>15} {}", "[Primary]", entry.primary.join(", "));
+            if !entry.secondary.is_empty() {
+                println!("{:>15} {}", "[Secondary]", entry.secondary.join(", "));
+            }
+            if !entry.aliases.is_empty() {
+                println!("{:>15} {}", "[Aliases]", entry.aliases.join(", "));
+            }
+            println!("{:>15} {}", "[Sessions]", logged.len());
+            if let Some(last) = logged.last() {
+                println!(
+                    "{:>15} {}",
+                    "[Time Elapsed]",
+                    format_elapsed(time_elapsed(last.finished_at()))
+                );
+            }
+        }
+        _ => unreachable!("should exhaustively check every parsed exercise subcommand"),
+    }
+    Ok(())
+}
+
+fn config_command(submatches: &ArgMatches, config: &Config, format: Format) -> Result<()> {
+    match submatches.subcommand() {
+        Some(("path", _)) => {
+            let path = match config.settings.backend {
+                BackendKind::Json => &config.storage_path,
+                BackendKind::Sqlite => &config.database_path,
+            };
+            let path = std::path::absolute(path).unwrap_or_else(|_| path.to_owned());
+            if format != Format::Human {
+                let mut table = Table::new(&["backend", "path"]);
+                table.push(vec![
+                    json!(config.settings.backend.to_string()),
+                    json!(path),
+                ]);
+                return table.print(format);
+            }
+            println!("{}", path.display());
+        }
+        _ => unreachable!("should exhaustively check every parsed config subcommand"),
+    }
+    Ok(())
+}
+
+fn profile_command(submatches: &ArgMatches, format: Format) -> Result<()> {
+    let profiles = Profiles::new(&Config::directory()?);
+    let name = |submatches: &ArgMatches| {
+        submatches
+            .get_one::<String>("name")
+            .expect("name should be parsed to be a valid string")
+            .to_owned()
+    };
+    match submatches.subcommand() {
+        Some(("list", _)) if format != Format::Human => {
+            let default = profiles.default_name()?;
+            let mut table = Table::new(&["name", "default"]);
+            for profile in profiles.list()? {
+                table.push(vec![json!(profile), json!(profile == default)]);
+            }
+            table.print(format)?;
+        }
+        Some(("list", _)) => {
+            let default = profiles.default_name()?;
+            for profile in profiles.list()? {
+                let marker = if profile == default { "*" } else { " " };
+                println!("{marker} {profile}");
+            }
+        }
+        Some(("create", submatches)) => profiles.create(&name(submatches))?,
+        Some(("delete", submatches)) => profiles.delete(&name(submatches))?,
+        Some(("rename", submatches)) => {
+            let new_name = submatches
+                .get_one::<String>("new-name")
+                .expect("new name should be parsed to be a valid string");
+            profiles.rename(&name(submatches), new_name)?
+        }
+        Some(("default", submatches)) => match submatches.get_one::<String>("name") {
+            Some(name) => profiles.set_default(name)?,
+            None => println!("{}", profiles.default_name()?),
+        },
+        _ => unreachable!("should exhaustively check every parsed profile subcommand"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> Session {
+        let timestamp = DateTime::parse_from_rfc3339("2026-10-12T18:00:00+02:00").unwrap();
+        let mut session = Session::new("Legs, \"heavy\"", timestamp);
+        session.identifier = "Ab3d".to_owned();
+        session.end = Some(timestamp + TimeDelta::minutes(75));
+        session.muscles = vec!["quads".to_owned(), "glutes".to_owned()];
+        session.tags = vec!["gym".to_owned()];
+        session.rpe = Some(8);
+        session.exercises = vec![exercise::parse("squat 5@100kg").unwrap()];
+        session
+    }
+
+    fn written(table: Table, format: Format) -> String {
+        let mut out = Vec::new();
+        table.write(&mut out, format).expect("written to memory");
+        String::from_utf8(out).expect("utf-8 output")
+    }
+
+    #[test]
+    fn session_table_json() {
+        let expected = r#"[
+  {
+    "identifier": "Ab3d",
+    "description": "Legs, \"heavy\"",
+    "timestamp": "2026-10-12T18:00:00+02:00",
+    "end": "2026-10-12T19:15:00+02:00",
+    "duration_minutes": 75,
+    "muscles": [
+      "quads",
+      "glutes"
+    ],
+    "tags": [
+      "gym"
+    ],
+    "rpe": 8,
+    "volume": null,
+    "exercise_volume": 500.0,
+    "exercises": [
+      {
+        "name": "squat",
+        "sets": [
+          {
+            "reps": 5,
+            "rir": null,
+            "rpe": null,
+            "weight": {
+              "unit": "kg",
+              "value": 100.0
+            }
+          }
+        ]
+      }
+    ]
+  }
+]
+"#;
+        let session = session();
+        assert_eq!(written(session_table([&session]), Format::Json), expected);
+    }
+
+    #[test]
+    fn session_table_jsonl() {
+        let expected = concat!(
+            r#"{"identifier":"Ab3d","description":"Legs, \"heavy\"","#,
+            r#""timestamp":"2026-10-12T18:00:00+02:00","end":"2026-10-12T19:15:00+02:00","#,
+            r#""duration_minutes":75,"muscles":["quads","glutes"],"tags":["gym"],"rpe":8,"#,
+            r#""volume":null,"exercise_volume":500.0,"exercises":[{"name":"squat","#,
+            r#""sets":[{"reps":5,"rir":null,"rpe":null,"weight":{"unit":"kg","value":100.0}}]}]}"#,
+            "\n",
+        );
+        let session = session();
+        assert_eq!(written(session_table([&session]), Format::Jsonl), expected);
+    }
+
+    #[test]
+    fn session_table_csv() {
+        let expected = concat!(
+            "identifier,description,timestamp,end,duration_minutes,muscles,tags,rpe,volume,",
+            "exercise_volume,exercises\n",
+            r#"Ab3d,"Legs, ""heavy""",2026-10-12T18:00:00+02:00,2026-10-12T19:15:00+02:00,"#,
+            r#"75,quads;glutes,gym,8,,500.0,"[{""name"":""squat"",""sets"":[{""reps"":5,"#,
+            r#"""rir"":null,""rpe"":null,""weight"":{""unit"":""kg"",""value"":100.0}}]}]""#,
+            "\n",
+        );
+        let session = session();
+        assert_eq!(written(session_table([&session]), Format::Csv), expected);
+    }
+
+    #[test]
+    fn session_table_tsv() {
+        let expected = concat!(
+            "identifier\tdescription\ttimestamp\tend\tduration_minutes\tmuscles\ttags\trpe\t",
+            "volume\texercise_volume\texercises\n",
+            "Ab3d\tLegs, \"heavy\"\t2026-10-12T18:00:00+02:00\t2026-10-12T19:15:00+02:00\t",
+            "75\tquads;glutes\tgym\t8\t\t500.0\t",
+            r#"[{"name":"squat","sets":[{"reps":5,"rir":null,"rpe":null,"#,
+            r#""weight":{"unit":"kg","value":100.0}}]}]"#,
+            "\n",
+        );
+        let session = session();
+        assert_eq!(written(session_table([&session]), Format::Tsv), expected);
+    }
+
+    #[test]
+    fn records_table_csv() {
+        let expected = "\
+exercise,record,reps,value_kg,previous_kg,identifier,timestamp
+squat,one_rep_max,,116.66666666666667,,Ab3d,2026-10-12T18:00:00+02:00
+squat,rep_max,5,100.0,,Ab3d,2026-10-12T18:00:00+02:00
+squat,volume,,500.0,,Ab3d,2026-10-12T18:00:00+02:00
+";
+        let session = session();
+        let history = records::history([&session], &RecordSettings::default());
+        assert_eq!(written(records_table(&history), Format::Csv), expected);
+    }
+
+    #[test]
+    fn stats_table_csv() {
+        let expected = "\
+statistic,key,value
+sessions,,1
+first,,2026-10-12T18:00:00+02:00
+last,,2026-10-12T18:00:00+02:00
+sessions_per_week,2026-W42,1
+sessions_per_month,2026-10,1
+average_gap_hours,,
+longest_gap_hours,,
+current_streak_days,,1
+longest_streak_days,,1
+busiest_weekday,Mon,1
+busiest_hour,18,1
+volume_by_muscle,glutes,500.0
+volume_by_muscle,hamstrings,500.0
+volume_by_muscle,lower-back,500.0
+volume_by_muscle,quads,500.0
+";
+        let session = session();
+        let today = NaiveDate::from_ymd_opt(2026, 10, 13).unwrap();
+        let stats = analytics::analyze(&[&session], today, &Catalog::built_in());
+        assert_eq!(written(stats_table(&stats), Format::Csv), expected);
+    }
+
+    #[test]
+    fn load_table_csv() {
+        let session = session();
+        let model = LoadModel::new(&LoadSettings::default());
+        let loads = daily_loads(&model, [&session]);
+        let days = model.daily(&loads, NaiveDate::from_ymd_opt(2026, 10, 13).unwrap());
+        let table = load_table(&days, &loads, &AcwrSettings::default());
+        let written = written(table, Format::Csv);
+        let lines: Vec<_> = written.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "date,load,fitness,fatigue,form,acwr");
+        assert_eq!(lines[1], "2026-10-12,8.0,8.0,8.0,0.0,");
+    }
+
+    #[test]
+    fn tags_table_csv() {
+        let expected = "\
+tag,sessions,last_used
+gym,1,2026-10-12T18:00:00+02:00
+";
+        let session = session();
+        let usage = [("gym", 1, session.timestamp)];
+        assert_eq!(written(tags_table(&usage), Format::Csv), expected);
+    }
+}
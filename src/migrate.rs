@@ -0,0 +1,156 @@
+use crate::timestamp;
+use anyhow::{Result, anyhow};
+use serde_json::{Value, json};
+
+/// Version of the storage format written by this build of `wr`
+pub const CURRENT_VERSION: u32 = 2;
+
+/// Upgrades storage contents from the version at its index to the next one
+type Migration = fn(&mut Value) -> Result<()>;
+
+const MIGRATIONS: [Migration; CURRENT_VERSION as usize] = [unversioned_to_v1, v1_to_v2];
+
+/// Version of stored contents, where files from before versioning count as version 0
+pub fn version(value: &Value) -> Result<u32> {
+    match value.get("version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| anyhow!("Storage version {version} is not a valid version number")),
+    }
+}
+
+/// Upgrades stored contents step by step up to the current version
+pub fn migrate(value: &mut Value) -> Result<()> {
+    let from = version(value)?;
+    if from > CURRENT_VERSION {
+        return Err(anyhow!(
+            "Storage was written by a newer version of wr (storage version {from}, \
+             supported up to {CURRENT_VERSION}). Please update wr."
+        ));
+    }
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        migration(value).map_err(|error| {
+            error.context(format!(
+                "Failed upgrading storage from version {version} to {}",
+                version + 1
+            ))
+        })?;
+    }
+    Ok(())
+}
+
+/// Sessions gained optional fields without a version, all of which have defaults
+fn unversioned_to_v1(value: &mut Value) -> Result<()> {
+    let storage = value
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("Storage is not a json object"))?;
+    if !storage.get("sessions").is_some_and(Value::is_array) {
+        return Err(anyhow!("Storage has no list of sessions"));
+    }
+    storage.insert("version".to_owned(), json!(1));
+    Ok(())
+}
+
+/// Timestamps changed from `SystemTime` objects to RFC 3339 strings with a UTC offset
+fn v1_to_v2(value: &mut Value) -> Result<()> {
+    let storage = value
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("Storage is not a json object"))?;
+    let sessions = storage
+        .get_mut("sessions")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| anyhow!("Storage has no list of sessions"))?;
+    for session in sessions {
+        convert_legacy_timestamps(session)?;
+    }
+    if let Some(active) = storage.get_mut("active").filter(|active| !active.is_null()) {
+        convert_legacy_timestamps(active)?;
+    }
+    storage.insert("version".to_owned(), json!(2));
+    Ok(())
+}
+
+fn convert_legacy_timestamps(session: &mut Value) -> Result<()> {
+    for field in ["timestamp", "end"] {
+        if let Some(timestamp) = session.get_mut(field).filter(|t| !t.is_null()) {
+            *timestamp = rfc3339_from_legacy(timestamp)?;
+        }
+    }
+    Ok(())
+}
+
+fn rfc3339_from_legacy(timestamp: &Value) -> Result<Value> {
+    let secs = timestamp.get("secs_since_epoch").and_then(Value::as_i64);
+    let nanos = timestamp
+        .get("nanos_since_epoch")
+        .and_then(Value::as_u64)
+        .and_then(|nanos| u32::try_from(nanos).ok());
+    let (Some(secs), Some(nanos)) = (secs, nanos) else {
+        return Err(anyhow!("Timestamp {timestamp} is not in the legacy format"));
+    };
+    let timestamp = timestamp::from_legacy(secs, nanos)
+        .ok_or_else(|| anyhow!("Timestamp {secs} seconds since epoch is out of range"))?;
+    Ok(json!(timestamp.to_rfc3339()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Storage;
+    use chrono::DateTime;
+
+    const V0: &str = include_str!("../tests/fixtures/storage-v0.json");
+    const V1: &str = include_str!("../tests/fixtures/storage-v1.json");
+
+    fn upgraded(contents: &str) -> Value {
+        let mut value: Value = serde_json::from_str(contents).expect("fixture is json");
+        migrate(&mut value).expect("fixture upgrades");
+        value
+    }
+
+    fn seconds(timestamp: &Value) -> i64 {
+        let timestamp = timestamp.as_str().expect("RFC 3339 string");
+        DateTime::parse_from_rfc3339(timestamp)
+            .expect("RFC 3339 timestamp")
+            .timestamp()
+    }
+
+    #[test]
+    fn unversioned_storage_upgrades_to_the_current_version() {
+        let value = upgraded(V0);
+        assert_eq!(version(&value).expect("version"), CURRENT_VERSION);
+        assert_eq!(seconds(&value["sessions"][0]["timestamp"]), 1735725600);
+        assert_eq!(seconds(&value["sessions"][1]["timestamp"]), 1735905600);
+        let storage: Storage = serde_json::from_value(value).expect("current storage");
+        assert_eq!(storage.sessions.len(), 2);
+        assert_eq!(storage.sessions[1].timestamp.timestamp_subsec_millis(), 250);
+    }
+
+    #[test]
+    fn version_1_storage_upgrades_to_the_current_version() {
+        let value = upgraded(V1);
+        assert_eq!(version(&value).expect("version"), CURRENT_VERSION);
+        assert_eq!(seconds(&value["sessions"][0]["end"]), 1735729200);
+        assert!(value["sessions"][1]["end"].is_null());
+        assert_eq!(seconds(&value["active"]["timestamp"]), 1735920000);
+        let storage: Storage = serde_json::from_value(value).expect("current storage");
+        assert_eq!(storage.sessions[0].rpe, Some(9));
+        assert!(storage.active.is_some());
+    }
+
+    #[test]
+    fn current_storage_is_left_alone() {
+        let current = upgraded(V1);
+        assert_eq!(upgraded(&current.to_string()), current);
+    }
+
+    #[test]
+    fn newer_storage_is_rejected() {
+        let mut value = json!({"version": CURRENT_VERSION + 1, "sessions": []});
+        let error = migrate(&mut value).expect_err("newer version").to_string();
+        assert!(error.contains("newer version of wr"), "{error}");
+        assert_eq!(value["version"], CURRENT_VERSION + 1);
+    }
+}
@@ -0,0 +1,254 @@
+use anyhow::{Context, Result};
+use serde::{Serialize, Serializer, ser::SerializeMap};
+use serde_json::Value;
+use std::io::{self, BufWriter, Write};
+
+/// Names accepted by `--format`, in the order shown in help
+pub const FORMATS: [&str; 5] = ["human", "json", "jsonl", "csv", "tsv"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// The layout meant for reading in a terminal
+    Human,
+    /// A single array of objects
+    Json,
+    /// One object per line
+    Jsonl,
+    Csv,
+    Tsv,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Format {
+        match name {
+            "json" => Format::Json,
+            "jsonl" => Format::Jsonl,
+            "csv" => Format::Csv,
+            "tsv" => Format::Tsv,
+            _ => Format::Human,
+        }
+    }
+}
+
+/// Rows of fields for the machine-readable formats. Column names are part of the output's
+/// interface, so rename them only with care.
+pub struct Table {
+    columns: Vec<&'static str>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+    pub fn new(columns: &[&'static str]) -> Self {
+        Table {
+            columns: columns.to_vec(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<Value>) {
+        debug_assert_eq!(
+            row.len(),
+            self.columns.len(),
+            "row should fill every column"
+        );
+        self.rows.push(row);
+    }
+
+    pub fn print(&self, format: Format) -> Result<()> {
+        let mut out = BufWriter::new(io::stdout().lock());
+        self.write(&mut out, format)
+            .and_then(|()| out.flush())
+            .context("Failed writing output")
+    }
+
+    pub fn write(&self, out: &mut impl Write, format: Format) -> io::Result<()> {
+        match format {
+            Format::Json => {
+                let objects: Vec<Object> = self.rows.iter().map(|row| self.object(row)).collect();
+                serde_json::to_writer_pretty(&mut *out, &objects)?;
+                writeln!(out)
+            }
+            Format::Jsonl => {
+                for row in &self.rows {
+                    serde_json::to_writer(&mut *out, &self.object(row))?;
+                    writeln!(out)?;
+                }
+                Ok(())
+            }
+            Format::Csv => self.write_delimited(out, ",", csv_field),
+            Format::Tsv => self.write_delimited(out, "\t", tsv_field),
+            Format::Human => unreachable!("commands should print the human format themselves"),
+        }
+    }
+
+    fn object<'a>(&'a self, row: &'a [Value]) -> Object<'a> {
+        Object {
+            columns: &self.columns,
+            row,
+        }
+    }
+
+    fn write_delimited(
+        &self,
+        out: &mut impl Write,
+        delimiter: &str,
+        escape: fn(String) -> String,
+    ) -> io::Result<()> {
+        writeln!(out, "{}", self.columns.join(delimiter))?;
+        for row in &self.rows {
+            let fields: Vec<String> = row.iter().map(|value| escape(flatten(value))).collect();
+            writeln!(out, "{}", fields.join(delimiter))?;
+        }
+        Ok(())
+    }
+}
+
+/// A row serialized as a json object with its fields in column order
+struct Object<'a> {
+    columns: &'a [&'static str],
+    row: &'a [Value],
+}
+
+impl Serialize for Object<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.columns.len()))?;
+        for (column, value) in self.columns.iter().zip(self.row) {
+            map.serialize_entry(column, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Renders a value as a single cell: null as empty, lists of plain values joined by `;`
+/// and anything more structured as compact json
+fn flatten(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(items)
+            if items
+                .iter()
+                .all(|item| !item.is_object() && !item.is_array()) =>
+        {
+            items.iter().map(flatten).collect::<Vec<_>>().join(";")
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Quotes fields as RFC 4180 requires
+fn csv_field(field: String) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+/// Tab separated values cannot quote, so tabs and line breaks become spaces
+fn tsv_field(field: String) -> String {
+    field.replace(['\t', '\n', '\r'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn table() -> Table {
+        let mut table = Table::new(&["identifier", "description", "rpe", "muscles", "exercises"]);
+        table.push(vec![
+            json!("Ab3d"),
+            json!("legs, \"heavy\"\nday"),
+            json!(8),
+            json!(["quads", "glutes"]),
+            json!([{"name": "squat", "sets": [{"reps": 5, "weight": 100.0}]}]),
+        ]);
+        table.push(vec![
+            json!("Xy7Q"),
+            json!("run\tslow"),
+            Value::Null,
+            json!([]),
+            json!([]),
+        ]);
+        table
+    }
+
+    fn written(format: Format) -> String {
+        let mut out = Vec::new();
+        table().write(&mut out, format).expect("written to memory");
+        String::from_utf8(out).expect("utf-8 output")
+    }
+
+    #[test]
+    fn json_keeps_the_column_order() {
+        let expected = r#"[
+  {
+    "identifier": "Ab3d",
+    "description": "legs, \"heavy\"\nday",
+    "rpe": 8,
+    "muscles": [
+      "quads",
+      "glutes"
+    ],
+    "exercises": [
+      {
+        "name": "squat",
+        "sets": [
+          {
+            "reps": 5,
+            "weight": 100.0
+          }
+        ]
+      }
+    ]
+  },
+  {
+    "identifier": "Xy7Q",
+    "description": "run\tslow",
+    "rpe": null,
+    "muscles": [],
+    "exercises": []
+  }
+]
+"#;
+        assert_eq!(written(Format::Json), expected);
+    }
+
+    #[test]
+    fn jsonl_writes_an_object_per_line() {
+        let expected = concat!(
+            r#"{"identifier":"Ab3d","description":"legs, \"heavy\"\nday","rpe":8,"#,
+            r#""muscles":["quads","glutes"],"#,
+            r#""exercises":[{"name":"squat","sets":[{"reps":5,"weight":100.0}]}]}"#,
+            "\n",
+            r#"{"identifier":"Xy7Q","description":"run\tslow","rpe":null,"muscles":[],"exercises":[]}"#,
+            "\n",
+        );
+        assert_eq!(written(Format::Jsonl), expected);
+    }
+
+    #[test]
+    fn csv_quotes_fields() {
+        let expected = concat!(
+            "identifier,description,rpe,muscles,exercises\n",
+            "Ab3d,\"legs, \"\"heavy\"\"\nday\",8,quads;glutes,",
+            r#""[{""name"":""squat"",""sets"":[{""reps"":5,""weight"":100.0}]}]""#,
+            "\n",
+            "Xy7Q,run\tslow,,,\n",
+        );
+        assert_eq!(written(Format::Csv), expected);
+    }
+
+    #[test]
+    fn tsv_replaces_tabs_and_line_breaks() {
+        let expected = concat!(
+            "identifier\tdescription\trpe\tmuscles\texercises\n",
+            "Ab3d\tlegs, \"heavy\" day\t8\tquads;glutes\t",
+            r#"[{"name":"squat","sets":[{"reps":5,"weight":100.0}]}]"#,
+            "\n",
+            "Xy7Q\trun slow\t\t\t\n",
+        );
+        assert_eq!(written(Format::Tsv), expected);
+    }
+}
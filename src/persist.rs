@@ -0,0 +1,222 @@
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions, TryLockError},
+    io::BufWriter,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Writes `value` as json to `path` without ever leaving a partially written file behind.
+/// The data goes to a temporary file in the same directory which, once synced to disk,
+/// is renamed over the original. Temporary files are named after the process, so that
+/// several `wr` processes writing the same file never replace each other's.
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    write_atomically(path, |writer| {
+        serde_json::to_writer(writer, value).context("Failed converting data into json")
+    })
+}
+
+pub fn write_json_pretty<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    write_atomically(path, |writer| {
+        serde_json::to_writer_pretty(writer, value).context("Failed converting data into json")
+    })
+}
+
+fn write_atomically(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<()>,
+) -> Result<()> {
+    let temp_path = sibling(path, &format!(".{}.tmp", std::process::id()))?;
+    let written = write_synced(&temp_path, write);
+    if let Err(error) = written {
+        let _ = fs::remove_file(&temp_path);
+        return Err(error.context(format!("Failed writing to {}", path.display())));
+    }
+    fs::rename(&temp_path, path)
+        .with_context(|| format!("Unable to replace {} when saving", path.display()))?;
+    sync_directory(path);
+    Ok(())
+}
+
+fn write_synced(path: &Path, write: impl FnOnce(&mut BufWriter<File>) -> Result<()>) -> Result<()> {
+    let file = File::create(path).context("Unable to create temporary file")?;
+    let mut writer = BufWriter::new(file);
+    write(&mut writer)?;
+    let file = writer
+        .into_inner()
+        .map_err(|error| anyhow!("Failed flushing temporary file: {}", error.error()))?;
+    file.sync_all()
+        .context("Failed syncing temporary file to disk")?;
+    Ok(())
+}
+
+/// Copies the current contents of `path`, if any, to a `.bak` file next to it
+pub fn back_up(path: &Path) -> Result<()> {
+    back_up_as(path, ".bak")
+}
+
+/// Copies the current contents of `path`, if any, next to it with `suffix` appended
+pub fn back_up_as(path: &Path, suffix: &str) -> Result<()> {
+    if path.exists() {
+        fs::copy(path, sibling(path, suffix)?)
+            .with_context(|| format!("Unable to back up {} before saving", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Moves `path`, if it exists, next to itself with `suffix` appended
+pub fn set_aside(path: &Path, suffix: &str) -> Result<()> {
+    if path.exists() {
+        fs::rename(path, sibling(path, suffix)?)
+            .with_context(|| format!("Unable to move {} out of the way", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Path next to `path` with `suffix` appended to its file name
+fn sibling(path: &Path, suffix: &str) -> Result<PathBuf> {
+    let mut name: OsString = path
+        .file_name()
+        .ok_or_else(|| anyhow!("{} is not a file path", path.display()))?
+        .to_owned();
+    name.push(suffix);
+    Ok(path.with_file_name(name))
+}
+
+/// Makes the rename durable, on a best-effort basis since failing here loses no data
+#[cfg(unix)]
+fn sync_directory(path: &Path) {
+    if let Some(directory) = path.parent()
+        && let Ok(directory) = File::open(directory)
+    {
+        let _ = directory.sync_all();
+    }
+}
+
+/// Directories cannot be opened and synced like files outside of unix
+#[cfg(not(unix))]
+fn sync_directory(_path: &Path) {}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct LockSettings {
+    /// How long to wait for another running `wr` to release the storage file
+    pub timeout_seconds: u64,
+}
+
+impl Default for LockSettings {
+    fn default() -> Self {
+        LockSettings {
+            timeout_seconds: 10,
+        }
+    }
+}
+
+/// Advisory lock serializing read-modify-write cycles across processes, released on drop
+pub struct FileLock {
+    _file: File,
+}
+
+/// Locks `path` exclusively, waiting up to `timeout` while another process holds it
+pub fn lock(path: &Path, timeout: Duration) -> Result<FileLock> {
+    // Read-only filesystems still allow locking an existing lock file opened for reading
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .or_else(|_| File::open(path))
+        .with_context(|| format!("Unable to open lock file {}", path.display()))?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(FileLock { _file: file }),
+            Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                thread::sleep(Duration::from_millis(50));
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(anyhow!(
+                    "Storage is in use by another `wr` process. Gave up waiting after {} seconds.",
+                    timeout.as_secs()
+                ));
+            }
+            Err(TryLockError::Error(error)) => {
+                return Err(error).with_context(|| format!("Unable to lock {}", path.display()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn file_names(directory: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(directory)
+            .expect("readable directory")
+            .map(|entry| entry.expect("directory entry").file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn failed_write_keeps_the_original() {
+        let directory = tempfile::tempdir().expect("temporary directory");
+        let path = directory.path().join("data.json");
+        fs::write(&path, "original").expect("original file");
+        let result = write_atomically(&path, |writer| {
+            writer.write_all(b"partial")?;
+            Err(anyhow!("interrupted"))
+        });
+        assert!(result.is_err());
+        assert_eq!(
+            fs::read_to_string(&path).expect("original file"),
+            "original"
+        );
+        assert_eq!(file_names(directory.path()), ["data.json"]);
+    }
+
+    #[test]
+    fn write_replaces_the_file() {
+        let directory = tempfile::tempdir().expect("temporary directory");
+        let path = directory.path().join("data.json");
+        fs::write(&path, "original").expect("original file");
+        write_json(&path, &[1, 2]).expect("written");
+        assert_eq!(fs::read_to_string(&path).expect("new file"), "[1,2]");
+        assert_eq!(file_names(directory.path()), ["data.json"]);
+    }
+
+    #[test]
+    fn back_up_copies_existing_files_only() {
+        let directory = tempfile::tempdir().expect("temporary directory");
+        let path = directory.path().join("data.json");
+        back_up(&path).expect("nothing to back up");
+        assert!(!sibling(&path, ".bak").expect("sibling path").exists());
+        fs::write(&path, "original").expect("original file");
+        back_up(&path).expect("backed up");
+        let backup = sibling(&path, ".bak").expect("sibling path");
+        assert_eq!(fs::read_to_string(backup).expect("backup"), "original");
+    }
+
+    #[test]
+    fn lock_gives_up_while_held() {
+        let directory = tempfile::tempdir().expect("temporary directory");
+        let path = directory.path().join("data.lock");
+        let held = lock(&path, Duration::ZERO).expect("first lock");
+        let started = Instant::now();
+        let error = lock(&path, Duration::from_millis(200))
+            .err()
+            .expect("lock should be in use")
+            .to_string();
+        assert!(error.contains("in use by another `wr` process"), "{error}");
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        drop(held);
+        lock(&path, Duration::ZERO).expect("lock after release");
+    }
+}
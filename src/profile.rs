@@ -0,0 +1,216 @@
+use crate::persist;
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+/// Profile stored directly in the configuration directory, as before profiles existed
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Serialize, Deserialize)]
+struct Registry {
+    default_profile: String,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Registry {
+            default_profile: DEFAULT_PROFILE.to_owned(),
+        }
+    }
+}
+
+/// Athlete profiles, each with its own directory of sessions and settings
+pub struct Profiles {
+    config_directory: PathBuf,
+}
+
+impl Profiles {
+    pub fn new(config_directory: &Path) -> Self {
+        Profiles {
+            config_directory: config_directory.to_owned(),
+        }
+    }
+
+    fn registry_path(&self) -> PathBuf {
+        self.config_directory.join("workout-recovery-profiles.json")
+    }
+
+    fn read_registry(&self) -> Result<Registry> {
+        let path = self.registry_path();
+        if !path.exists() {
+            return Ok(Registry::default());
+        }
+        let file = File::open(&path).context("Unable to read existing profiles file")?;
+        serde_json::from_reader(file).context("Failed converting profiles file contents into json")
+    }
+
+    fn directory(&self, name: &str) -> PathBuf {
+        if name == DEFAULT_PROFILE {
+            self.config_directory.clone()
+        } else {
+            self.config_directory.join("profiles").join(name)
+        }
+    }
+
+    fn exists(&self, name: &str) -> bool {
+        name == DEFAULT_PROFILE || self.directory(name).is_dir()
+    }
+
+    pub fn default_name(&self) -> Result<String> {
+        Ok(self.read_registry()?.default_profile)
+    }
+
+    /// Name and directory of the requested profile, or of the default one
+    pub fn resolve(&self, requested: Option<&str>) -> Result<(String, PathBuf)> {
+        let name = match requested {
+            Some(name) => name.to_owned(),
+            None => self.default_name()?,
+        };
+        validate_name(&name)?;
+        if !self.exists(&name) {
+            return Err(anyhow!(
+                "Profile {name} does not exist. Create it with `profile create {name}`."
+            ));
+        }
+        let directory = self.directory(&name);
+        Ok((name, directory))
+    }
+
+    pub fn list(&self) -> Result<Vec<String>> {
+        let mut names = vec![DEFAULT_PROFILE.to_owned()];
+        let profiles_directory = self.config_directory.join("profiles");
+        if profiles_directory.is_dir() {
+            for entry in
+                fs::read_dir(&profiles_directory).context("Unable to read profiles directory")?
+            {
+                let entry = entry.context("Unable to read profiles directory")?;
+                if entry.path().is_dir() {
+                    names.push(entry.file_name().to_string_lossy().into_owned());
+                }
+            }
+        }
+        names[1..].sort();
+        Ok(names)
+    }
+
+    pub fn create(&self, name: &str) -> Result<()> {
+        validate_name(name)?;
+        if self.exists(name) {
+            return Err(anyhow!("Profile {name} already exists"));
+        }
+        fs::create_dir_all(self.directory(name))
+            .with_context(|| format!("Unable to create directory for profile {name}"))?;
+        println!("Created profile {name}");
+        Ok(())
+    }
+
+    pub fn delete(&self, name: &str) -> Result<()> {
+        validate_name(name)?;
+        if name == DEFAULT_PROFILE {
+            return Err(anyhow!("The {DEFAULT_PROFILE} profile cannot be deleted"));
+        }
+        if !self.exists(name) {
+            return Err(anyhow!("Profile {name} does not exist"));
+        }
+        if self.default_name()? == name {
+            return Err(anyhow!(
+                "Profile {name} is the default profile. Choose another default first."
+            ));
+        }
+        fs::remove_dir_all(self.directory(name))
+            .with_context(|| format!("Unable to delete directory of profile {name}"))?;
+        println!("Deleted profile {name}");
+        Ok(())
+    }
+
+    pub fn rename(&self, from: &str, to: &str) -> Result<()> {
+        if from == DEFAULT_PROFILE {
+            return Err(anyhow!("The {DEFAULT_PROFILE} profile cannot be renamed"));
+        }
+        validate_name(from)?;
+        validate_name(to)?;
+        if !self.exists(from) {
+            return Err(anyhow!("Profile {from} does not exist"));
+        }
+        if self.exists(to) {
+            return Err(anyhow!("Profile {to} already exists"));
+        }
+        fs::rename(self.directory(from), self.directory(to))
+            .with_context(|| format!("Unable to rename directory of profile {from}"))?;
+        if self.default_name()? == from {
+            self.write_default(to)?;
+        }
+        println!("Renamed profile {from} to {to}");
+        Ok(())
+    }
+
+    pub fn set_default(&self, name: &str) -> Result<()> {
+        validate_name(name)?;
+        if !self.exists(name) {
+            return Err(anyhow!("Profile {name} does not exist"));
+        }
+        self.write_default(name)?;
+        println!("Profile {name} is now the default profile");
+        Ok(())
+    }
+
+    fn write_default(&self, name: &str) -> Result<()> {
+        let registry = Registry {
+            default_profile: name.to_owned(),
+        };
+        persist::write_json_pretty(&self.registry_path(), &registry)
+            .context("Failed writing to profiles file")
+    }
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(anyhow!(
+            "Profile names may only contain letters, digits, '-' and '_'"
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_finds_existing_profiles() {
+        let directory = tempfile::tempdir().expect("temporary directory");
+        let profiles = Profiles::new(directory.path());
+        let (name, path) = profiles.resolve(None).expect("default profile");
+        assert_eq!(
+            (name.as_str(), path.as_path()),
+            (DEFAULT_PROFILE, directory.path())
+        );
+        assert!(profiles.resolve(Some("alice")).is_err());
+        profiles.create("alice").expect("created");
+        let (name, path) = profiles.resolve(Some("alice")).expect("created profile");
+        assert_eq!(name, "alice");
+        assert_eq!(path, directory.path().join("profiles").join("alice"));
+    }
+
+    #[test]
+    fn names_outside_the_profiles_directory_are_rejected() {
+        let directory = tempfile::tempdir().expect("temporary directory");
+        let profiles = Profiles::new(directory.path());
+        profiles.create("alice").expect("created");
+        for name in ["..", ".", "alice/..", "../profiles/alice", ""] {
+            assert!(profiles.resolve(Some(name)).is_err(), "resolved {name:?}");
+            assert!(profiles.set_default(name).is_err(), "made {name:?} default");
+            assert!(profiles.delete(name).is_err(), "deleted {name:?}");
+            assert!(profiles.rename(name, "bob").is_err(), "renamed {name:?}");
+        }
+        assert!(directory.path().join("profiles").join("alice").is_dir());
+        assert_eq!(profiles.default_name().expect("default"), DEFAULT_PROFILE);
+    }
+}
@@ -0,0 +1,267 @@
+use crate::{Session, timestamp, timestamp::Timestamp};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Days, Local, NaiveDate};
+use clap::{Arg, ArgAction, ArgMatches, builder::NonEmptyStringValueParser, value_parser};
+use regex::Regex;
+
+/// Selection of workout sessions shared by every command that reads several of them
+#[derive(Debug)]
+pub struct Query {
+    since: Option<Timestamp>,
+    /// Exclusive upper bound
+    until: Option<Timestamp>,
+    pattern: Option<Regex>,
+    muscles: Vec<String>,
+    tags: Vec<String>,
+    reverse: bool,
+    /// Number of most recent matching sessions to keep
+    limit: Option<usize>,
+}
+
+impl Query {
+    /// Arguments choosing which sessions a query matches, for commands to add alongside
+    /// their own
+    pub fn filter_args() -> [Arg; 5] {
+        [
+            Arg::new("since")
+                .long("since")
+                .action(ArgAction::Set)
+                .value_parser(NonEmptyStringValueParser::new())
+                .help("Only sessions from this day or time on (e.g. 2026-10-01, \"3d ago\")"),
+            Arg::new("until")
+                .long("until")
+                .action(ArgAction::Set)
+                .value_parser(NonEmptyStringValueParser::new())
+                .help("Only sessions up to this day or time (e.g. 2026-10-31, yesterday)"),
+            Arg::new("grep")
+                .long("grep")
+                .action(ArgAction::Set)
+                .value_parser(parse_pattern)
+                .help("Only sessions whose description matches this regular expression"),
+            Arg::new("muscle")
+                .long("muscle")
+                .action(ArgAction::Append)
+                .value_delimiter(',')
+                .value_parser(NonEmptyStringValueParser::new())
+                .help("Only sessions training any of these muscle groups"),
+            Arg::new("tag")
+                .long("tag")
+                .action(ArgAction::Append)
+                .value_delimiter(',')
+                .value_parser(NonEmptyStringValueParser::new())
+                .help("Only sessions tagged with any of these tags"),
+        ]
+    }
+
+    /// Arguments choosing how many of the matching sessions to list and in which order
+    pub fn presentation_args() -> [Arg; 3] {
+        [
+            Arg::new("reverse")
+                .long("reverse")
+                .action(ArgAction::SetTrue)
+                .help("Show the most recent sessions first"),
+            Arg::new("number")
+                .short('n')
+                .long("number")
+                .action(ArgAction::Set)
+                .value_parser(value_parser!(usize))
+                .conflicts_with("all")
+                .help("Number of sessions to display"),
+            Arg::new("all")
+                .long("all")
+                .action(ArgAction::SetTrue)
+                .help("Display every matching session"),
+        ]
+    }
+
+    /// Reads the filter arguments into a query selecting every matching session
+    pub fn from_matches(submatches: &ArgMatches) -> Result<Self> {
+        Ok(Query {
+            since: bound(submatches, "since", false)?,
+            until: bound(submatches, "until", true)?,
+            pattern: submatches.get_one::<Regex>("grep").cloned(),
+            muscles: labels(submatches, "muscle"),
+            tags: labels(submatches, "tag"),
+            reverse: false,
+            limit: None,
+        })
+    }
+
+    /// Reads the presentation arguments, keeping the last `default_limit` sessions unless
+    /// `--number` or `--all` are given
+    pub fn presented(self, submatches: &ArgMatches, default_limit: Option<usize>) -> Self {
+        let limit = if submatches.get_flag("all") {
+            None
+        } else {
+            submatches
+                .get_one::<usize>("number")
+                .copied()
+                .or(default_limit)
+        };
+        Query {
+            reverse: submatches.get_flag("reverse"),
+            limit,
+            ..self
+        }
+    }
+
+    pub fn matches(&self, session: &Session) -> bool {
+        self.since.is_none_or(|since| session.timestamp >= since)
+            && self.until.is_none_or(|until| session.timestamp < until)
+            && self
+                .pattern
+                .as_ref()
+                .is_none_or(|pattern| pattern.is_match(&session.description))
+            && (self.muscles.is_empty() || session.muscles.iter().any(|m| self.muscles.contains(m)))
+            && (self.tags.is_empty() || session.tags.iter().any(|t| self.tags.contains(t)))
+    }
+
+    /// Matching sessions out of `sessions`, which are in chronological order
+    pub fn select<'a>(&self, sessions: &'a [Session]) -> Vec<&'a Session> {
+        let mut selected: Vec<&Session> = sessions.iter().filter(|s| self.matches(s)).collect();
+        if let Some(limit) = self.limit {
+            selected.drain(..selected.len().saturating_sub(limit));
+        }
+        if self.reverse {
+            selected.reverse();
+        }
+        selected
+    }
+}
+
+fn labels(submatches: &ArgMatches, id: &str) -> Vec<String> {
+    submatches
+        .get_many::<String>(id)
+        .map(|labels| labels.map(|label| label.trim().to_lowercase()).collect())
+        .unwrap_or_default()
+}
+
+fn parse_pattern(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern).context("pattern should be a valid regular expression")
+}
+
+/// Parses `--since` or `--until`
+fn bound(submatches: &ArgMatches, id: &str, upper: bool) -> Result<Option<Timestamp>> {
+    submatches
+        .get_one::<String>(id)
+        .map(|input| parse_bound(input, upper, Local::now()))
+        .transpose()
+}
+
+/// Parses a bound relative to `now`. Plain dates, `today` and `yesterday` stand for whole
+/// days, so as upper bound they include the end of that day.
+fn parse_bound(input: &str, upper: bool, now: DateTime<Local>) -> Result<Timestamp> {
+    let input = input.trim();
+    let date = match input.to_lowercase().as_str() {
+        "today" => Some(now.date_naive()),
+        "yesterday" => now.date_naive().checked_sub_days(Days::new(1)),
+        _ => NaiveDate::parse_from_str(input, "%Y-%m-%d").ok(),
+    };
+    match date {
+        Some(date) if upper => {
+            let next_day = date
+                .checked_add_days(Days::new(1))
+                .context("Date is out of range")?;
+            timestamp::start_of_day(next_day)
+        }
+        Some(date) => timestamp::start_of_day(date),
+        None => timestamp::parse_at(input, now),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeDelta, TimeZone};
+    use clap::Command;
+
+    fn now() -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2026, 10, 18, 15, 30, 0)
+            .single()
+            .expect("unambiguous local time")
+    }
+
+    fn midnight(date: &str) -> Timestamp {
+        timestamp::start_of_day(date.parse().expect("test date")).expect("local midnight")
+    }
+
+    fn session(identifier: &str, timestamp: Timestamp, tags: &[&str]) -> Session {
+        let mut session = Session::new(identifier, timestamp);
+        session.identifier = identifier.to_owned();
+        session.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        session
+    }
+
+    fn query(args: &[&str]) -> Query {
+        let command = Command::new("list")
+            .args(Query::filter_args())
+            .args(Query::presentation_args());
+        let matches = command
+            .try_get_matches_from(std::iter::once("list").chain(args.iter().copied()))
+            .expect("valid arguments");
+        Query::from_matches(&matches)
+            .expect("valid query")
+            .presented(&matches, Some(10))
+    }
+
+    fn identifiers(sessions: Vec<&Session>) -> Vec<&str> {
+        sessions.iter().map(|s| s.identifier.as_str()).collect()
+    }
+
+    #[test]
+    fn dates_bound_whole_days() {
+        for (input, since, until) in [
+            ("2026-10-01", "2026-10-01", "2026-10-02"),
+            ("today", "2026-10-18", "2026-10-19"),
+            (" Yesterday ", "2026-10-17", "2026-10-18"),
+        ] {
+            let bound = |upper| parse_bound(input, upper, now()).expect("valid bound");
+            assert_eq!(bound(false), midnight(since), "--since {input}");
+            assert_eq!(bound(true), midnight(until), "--until {input}");
+        }
+    }
+
+    #[test]
+    fn times_bound_exactly() {
+        let at = |input| parse_bound(input, true, now()).expect("valid bound");
+        assert_eq!(at("3h ago"), (now() - TimeDelta::hours(3)).fixed_offset());
+        assert_eq!(
+            at("yesterday 18:00"),
+            midnight("2026-10-17") + TimeDelta::hours(18)
+        );
+        assert_eq!(
+            at("2026-10-01 07:30"),
+            midnight("2026-10-01") + TimeDelta::minutes(450)
+        );
+        assert!(parse_bound("someday", false, now()).is_err());
+    }
+
+    #[test]
+    fn select_filters_then_limits_then_reverses() {
+        let day = |date| midnight(date) + TimeDelta::hours(9);
+        let sessions: Vec<Session> = [
+            ("A", "2026-10-01", &["gym"][..]),
+            ("B", "2026-10-02", &[]),
+            ("C", "2026-10-03", &["gym"]),
+            ("D", "2026-10-04", &["gym"]),
+            ("E", "2026-10-05", &["gym"]),
+        ]
+        .into_iter()
+        .map(|(identifier, date, tags)| session(identifier, day(date), tags))
+        .collect();
+        let selected = |args: &[&str]| identifiers(query(args).select(&sessions));
+        assert_eq!(selected(&[]), ["A", "B", "C", "D", "E"]);
+        assert_eq!(selected(&["-n", "2"]), ["D", "E"]);
+        assert_eq!(selected(&["-n", "2", "--reverse"]), ["E", "D"]);
+        assert_eq!(
+            selected(&["--tag", "gym", "-n", "3", "--reverse"]),
+            ["E", "D", "C"]
+        );
+        assert_eq!(selected(&["--until", "2026-10-03", "-n", "2"]), ["B", "C"]);
+        assert_eq!(
+            selected(&["--since", "2026-10-04", "--all", "--reverse"]),
+            ["E", "D"]
+        );
+    }
+}
@@ -0,0 +1,328 @@
+use crate::{Session, timestamp::Timestamp};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+/// Improvements smaller than this, in kilograms, are rounding noise rather than records
+const TOLERANCE: f64 = 1e-6;
+
+/// Formula estimating the weight that could be lifted for a single rep
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OneRepMaxFormula {
+    /// weight × (1 + reps / 30)
+    #[default]
+    Epley,
+    /// weight × 36 / (37 − reps)
+    Brzycki,
+}
+
+impl OneRepMaxFormula {
+    pub fn estimate(self, weight: f64, reps: u32) -> f64 {
+        if reps == 1 {
+            return weight;
+        }
+        let reps = f64::from(reps);
+        match self {
+            OneRepMaxFormula::Epley => weight * (1.0 + reps / 30.0),
+            OneRepMaxFormula::Brzycki => weight * 36.0 / (37.0 - reps),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RecordSettings {
+    pub one_rep_max_formula: OneRepMaxFormula,
+    /// Sets with more reps are too far from a single rep to estimate it
+    pub max_estimate_reps: u32,
+}
+
+impl Default for RecordSettings {
+    fn default() -> Self {
+        RecordSettings {
+            one_rep_max_formula: OneRepMaxFormula::default(),
+            max_estimate_reps: 12,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Kind {
+    /// Estimated weight for a single rep
+    OneRepMax,
+    /// Heaviest set of exactly this many reps
+    Reps(u32),
+    /// Most volume lifted in one session
+    Volume,
+}
+
+impl Kind {
+    /// Name of the kind of record in machine-readable output
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::OneRepMax => "one_rep_max",
+            Kind::Reps(_) => "rep_max",
+            Kind::Volume => "volume",
+        }
+    }
+
+    pub fn reps(self) -> Option<u32> {
+        match self {
+            Kind::Reps(reps) => Some(reps),
+            Kind::OneRepMax | Kind::Volume => None,
+        }
+    }
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Kind::OneRepMax => write!(f, "estimated 1RM"),
+            Kind::Reps(reps) => write!(f, "{reps} rep max"),
+            Kind::Volume => write!(f, "volume"),
+        }
+    }
+}
+
+/// A personal record for an exercise and the session that set it
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub exercise: String,
+    pub kind: Kind,
+    /// Weight or volume in kilograms
+    pub value: f64,
+    /// Record that was beaten, if the exercise had been logged before
+    pub previous: Option<f64>,
+    pub identifier: String,
+    pub timestamp: Timestamp,
+}
+
+impl Display for Record {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} {} {:.1} kg", self.exercise, self.kind, self.value)?;
+        if let Some(previous) = self.previous {
+            write!(f, " (previously {previous:.1} kg)")?;
+        }
+        Ok(())
+    }
+}
+
+type Bests = BTreeMap<(String, Kind), f64>;
+
+/// Best values a single session reached for each exercise
+fn session_bests(session: &Session, settings: &RecordSettings) -> Bests {
+    let mut bests = Bests::new();
+    let mut raise = |key: (String, Kind), value: f64| {
+        let best = bests.entry(key).or_insert(value);
+        *best = best.max(value);
+    };
+    let mut volumes: BTreeMap<&str, f64> = BTreeMap::new();
+    for exercise in &session.exercises {
+        *volumes.entry(&exercise.name).or_default() += exercise.volume();
+        for set in &exercise.sets {
+            let Some(weight) = set.weight.map(|weight| weight.kilograms()) else {
+                continue;
+            };
+            if weight <= 0.0 {
+                continue;
+            }
+            raise((exercise.name.clone(), Kind::Reps(set.reps)), weight);
+            if set.reps <= settings.max_estimate_reps {
+                let estimate = settings.one_rep_max_formula.estimate(weight, set.reps);
+                raise((exercise.name.clone(), Kind::OneRepMax), estimate);
+            }
+        }
+    }
+    for (name, volume) in volumes {
+        if volume > 0.0 {
+            raise((name.to_owned(), Kind::Volume), volume);
+        }
+    }
+    bests
+}
+
+/// Compares a session against the bests so far, raising them and returning its records
+fn improve(bests: &mut Bests, session: &Session, settings: &RecordSettings) -> Vec<Record> {
+    let mut records = Vec::new();
+    for (key, value) in session_bests(session, settings) {
+        let previous = bests.get(&key).copied();
+        if previous.is_some_and(|previous| value <= previous + TOLERANCE) {
+            continue;
+        }
+        records.push(Record {
+            exercise: key.0.clone(),
+            kind: key.1,
+            value,
+            previous,
+            identifier: session.identifier.clone(),
+            timestamp: session.timestamp,
+        });
+        bests.insert(key, value);
+    }
+    records
+}
+
+/// Every record set over the given sessions, which must be in chronological order
+pub fn history<'a>(
+    sessions: impl IntoIterator<Item = &'a Session>,
+    settings: &RecordSettings,
+) -> Vec<Record> {
+    let mut bests = Bests::new();
+    sessions
+        .into_iter()
+        .flat_map(|session| improve(&mut bests, session, settings))
+        .collect()
+}
+
+/// Records beaten by a new session compared to the sessions before it. Exercises logged
+/// for the first time set no records worth announcing.
+pub fn detect(sessions: &[Session], session: &Session, settings: &RecordSettings) -> Vec<Record> {
+    let mut bests = Bests::new();
+    for earlier in sessions.iter().filter(|s| s.timestamp <= session.timestamp) {
+        improve(&mut bests, earlier, settings);
+    }
+    improve(&mut bests, session, settings)
+        .into_iter()
+        .filter(|record| record.previous.is_some())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exercise;
+    use chrono::DateTime;
+
+    fn session(identifier: &str, day: u32, exercises: &[&str]) -> Session {
+        let timestamp = format!("2026-10-{day:02}T18:00:00+00:00");
+        let timestamp = DateTime::parse_from_rfc3339(&timestamp).expect("test timestamp");
+        let mut session = Session::new(identifier, timestamp);
+        session.identifier = identifier.to_owned();
+        session.exercises = exercises
+            .iter()
+            .map(|e| exercise::parse(e).expect("test exercise"))
+            .collect();
+        session
+    }
+
+    fn summary(records: &[Record]) -> Vec<(String, Kind, f64, Option<f64>)> {
+        records
+            .iter()
+            .map(|r| {
+                let round = |value: f64| (value * 100.0).round() / 100.0;
+                (
+                    format!("{} {}", r.identifier, r.exercise),
+                    r.kind,
+                    round(r.value),
+                    r.previous.map(round),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn formulas_estimate_a_single_rep() {
+        let epley = OneRepMaxFormula::Epley;
+        let brzycki = OneRepMaxFormula::Brzycki;
+        assert!((epley.estimate(100.0, 5) - 116.666_666).abs() < 1e-3);
+        assert!((brzycki.estimate(100.0, 5) - 112.5).abs() < 1e-9);
+        assert_eq!(epley.estimate(140.0, 1), 140.0);
+        assert_eq!(brzycki.estimate(140.0, 1), 140.0);
+    }
+
+    #[test]
+    fn history_records_bests_per_rep_count_and_volume() {
+        let sessions = [
+            session("A", 1, &["bench 3x5@100kg"]),
+            session("B", 3, &["bench 3@110kg"]),
+            session("C", 5, &["bench 5@100kg, 4x5@90kg"]),
+        ];
+        let records = history(&sessions, &RecordSettings::default());
+        let bench = |identifier: &str| format!("{identifier} bench");
+        assert_eq!(
+            summary(&records),
+            [
+                (bench("A"), Kind::OneRepMax, 116.67, None),
+                (bench("A"), Kind::Reps(5), 100.0, None),
+                (bench("A"), Kind::Volume, 1500.0, None),
+                (bench("B"), Kind::OneRepMax, 121.0, Some(116.67)),
+                (bench("B"), Kind::Reps(3), 110.0, None),
+                (bench("C"), Kind::Volume, 2300.0, Some(1500.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn volume_adds_up_every_entry_of_an_exercise() {
+        let sessions = [
+            session("A", 1, &["squat 5@100kg"]),
+            session("B", 2, &["squat 3@100kg", "squat 3@100kg"]),
+        ];
+        let records = history(&sessions, &RecordSettings::default());
+        let volume: Vec<(&str, f64)> = records
+            .iter()
+            .filter(|r| r.kind == Kind::Volume)
+            .map(|r| (r.identifier.as_str(), r.value))
+            .collect();
+        assert_eq!(volume, [("A", 500.0), ("B", 600.0)]);
+    }
+
+    #[test]
+    fn ties_within_tolerance_are_not_records() {
+        let sessions = [
+            session("A", 1, &["row 5@60kg"]),
+            session("B", 2, &["row 5@60.0000001kg"]),
+            session("C", 3, &["row 5@60.01kg"]),
+        ];
+        let records = history(&sessions, &RecordSettings::default());
+        assert!(records.iter().all(|r| r.identifier != "B"));
+        assert!(
+            records
+                .iter()
+                .any(|r| r.identifier == "C" && r.kind == Kind::Reps(5))
+        );
+    }
+
+    #[test]
+    fn high_rep_and_unweighted_sets_are_not_estimated() {
+        let sessions = [session("A", 1, &["curl 15@20kg", "dips 3x10"])];
+        let records = history(&sessions, &RecordSettings::default());
+        let kinds: Vec<(&str, Kind)> = records
+            .iter()
+            .map(|r| (r.exercise.as_str(), r.kind))
+            .collect();
+        assert_eq!(kinds, [("curl", Kind::Reps(15)), ("curl", Kind::Volume)]);
+    }
+
+    #[test]
+    fn detect_skips_exercises_logged_for_the_first_time() {
+        let sessions = [session("A", 1, &["bench 5@100kg"])];
+        let new = session("B", 2, &["bench 5@105kg", "ohp 5@50kg"]);
+        let records = detect(&sessions, &new, &RecordSettings::default());
+        assert!(!records.is_empty());
+        assert!(records.iter().all(|r| r.exercise == "bench"));
+        assert!(records.iter().all(|r| r.previous.is_some()));
+    }
+
+    #[test]
+    fn detect_compares_a_backdated_session_with_earlier_ones_only() {
+        let sessions = [
+            session("A", 1, &["bench 5@100kg"]),
+            session("C", 10, &["bench 5@120kg"]),
+        ];
+        let backdated = session("B", 5, &["bench 5@110kg"]);
+        let records = detect(&sessions, &backdated, &RecordSettings::default());
+        let bench = "B bench".to_owned();
+        assert_eq!(
+            summary(&records),
+            [
+                (bench.clone(), Kind::OneRepMax, 128.33, Some(116.67)),
+                (bench.clone(), Kind::Reps(5), 110.0, Some(100.0)),
+                (bench, Kind::Volume, 550.0, Some(500.0)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,311 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display, Formatter},
+};
+
+/// Multiple of the recovery window after which a muscle group counts as fresh
+const FRESH_MULTIPLIER: f64 = 2.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RecoverySettings {
+    pub default_window_hours: u32,
+    pub window_hours: BTreeMap<String, u32>,
+    pub intensity: IntensitySettings,
+}
+
+impl Default for RecoverySettings {
+    fn default() -> Self {
+        let large_muscles = ["legs", "quads", "hamstrings", "glutes", "lower-back"];
+        RecoverySettings {
+            default_window_hours: 48,
+            window_hours: large_muscles
+                .into_iter()
+                .map(|muscle| (muscle.to_owned(), 72))
+                .collect(),
+            intensity: IntensitySettings::default(),
+        }
+    }
+}
+
+/// Selects and tunes the function scaling recovery windows by session intensity
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IntensitySettings {
+    /// Every session uses the unscaled recovery window
+    None,
+    /// Windows grow in proportion to RPE and volume relative to a baseline session
+    Proportional {
+        baseline_rpe: f64,
+        baseline_volume: Option<f64>,
+        min_factor: f64,
+        max_factor: f64,
+    },
+}
+
+impl Default for IntensitySettings {
+    fn default() -> Self {
+        IntensitySettings::Proportional {
+            baseline_rpe: 7.0,
+            baseline_volume: None,
+            min_factor: 0.5,
+            max_factor: 2.0,
+        }
+    }
+}
+
+impl IntensitySettings {
+    pub fn scaling(&self) -> Box<dyn IntensityScaling> {
+        match *self {
+            IntensitySettings::None => Box::new(Unscaled),
+            IntensitySettings::Proportional {
+                baseline_rpe,
+                baseline_volume,
+                min_factor,
+                max_factor,
+            } => Box::new(Proportional {
+                baseline_rpe,
+                baseline_volume,
+                min_factor,
+                max_factor,
+            }),
+        }
+    }
+}
+
+/// How hard a single session was, as far as it was recorded
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Intensity {
+    pub rpe: Option<u8>,
+    pub volume: Option<f64>,
+}
+
+pub trait IntensityScaling {
+    /// Factor applied to a recovery window for a session of the given intensity
+    fn factor(&self, intensity: &Intensity) -> f64;
+}
+
+pub struct Unscaled;
+
+impl IntensityScaling for Unscaled {
+    fn factor(&self, _intensity: &Intensity) -> f64 {
+        1.0
+    }
+}
+
+pub struct Proportional {
+    pub baseline_rpe: f64,
+    pub baseline_volume: Option<f64>,
+    pub min_factor: f64,
+    pub max_factor: f64,
+}
+
+impl IntensityScaling for Proportional {
+    fn factor(&self, intensity: &Intensity) -> f64 {
+        let mut factor = 1.0;
+        if let Some(rpe) = intensity.rpe
+            && self.baseline_rpe > 0.0
+        {
+            factor *= f64::from(rpe) / self.baseline_rpe;
+        }
+        if let (Some(volume), Some(baseline_volume)) = (intensity.volume, self.baseline_volume)
+            && baseline_volume > 0.0
+        {
+            factor *= volume / baseline_volume;
+        }
+        factor.clamp(self.min_factor, self.max_factor)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    /// Trained within the recovery window
+    Recovering,
+    /// The recovery window has passed
+    Ready,
+    /// Untrained for well beyond the recovery window
+    Fresh,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let label = match self {
+            Status::Recovering => "Recovering",
+            Status::Ready => "Ready",
+            Status::Fresh => "Fresh",
+        };
+        f.pad(label)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Readiness {
+    pub elapsed: TimeDelta,
+    pub window: TimeDelta,
+    pub percent_recovered: f64,
+    pub status: Status,
+}
+
+pub struct RecoveryModel {
+    default_window: TimeDelta,
+    windows: HashMap<String, TimeDelta>,
+    scaling: Box<dyn IntensityScaling>,
+}
+
+impl RecoveryModel {
+    pub fn new(settings: &RecoverySettings) -> Self {
+        Self::with_scaling(settings, settings.intensity.scaling())
+    }
+
+    pub fn with_scaling(settings: &RecoverySettings, scaling: Box<dyn IntensityScaling>) -> Self {
+        RecoveryModel {
+            default_window: hours(settings.default_window_hours),
+            windows: settings
+                .window_hours
+                .iter()
+                .map(|(muscle, window)| (muscle.to_lowercase(), hours(*window)))
+                .collect(),
+            scaling,
+        }
+    }
+
+    pub fn window(&self, muscle: &str, intensity: &Intensity) -> TimeDelta {
+        let base = self
+            .windows
+            .get(muscle)
+            .copied()
+            .unwrap_or(self.default_window);
+        let factor = self.scaling.factor(intensity).max(0.0);
+        TimeDelta::seconds((base.num_seconds() as f64 * factor).round() as i64)
+    }
+
+    /// Assesses a muscle group after a single session at `trained_at`, as seen from `now`
+    pub fn assess(
+        &self,
+        muscle: &str,
+        trained_at: DateTime<Utc>,
+        intensity: &Intensity,
+        now: DateTime<Utc>,
+    ) -> Readiness {
+        let window = self.window(muscle, intensity);
+        let elapsed = (now - trained_at).max(TimeDelta::zero());
+        let ratio = if window > TimeDelta::zero() {
+            elapsed.num_seconds() as f64 / window.num_seconds() as f64
+        } else {
+            f64::INFINITY
+        };
+        let status = if ratio >= FRESH_MULTIPLIER {
+            Status::Fresh
+        } else if ratio >= 1.0 {
+            Status::Ready
+        } else {
+            Status::Recovering
+        };
+        Readiness {
+            elapsed,
+            window,
+            percent_recovered: (ratio * 100.0).min(100.0),
+            status,
+        }
+    }
+
+    /// Assesses a muscle group over all sessions training it, limited by the least recovered one
+    pub fn assess_all(
+        &self,
+        muscle: &str,
+        trainings: &[(DateTime<Utc>, Intensity)],
+        now: DateTime<Utc>,
+    ) -> Option<Readiness> {
+        let last_trained = trainings.iter().map(|(trained_at, _)| *trained_at).max()?;
+        let limiting = trainings
+            .iter()
+            .map(|(trained_at, intensity)| self.assess(muscle, *trained_at, intensity, now))
+            .min_by(|a, b| {
+                a.status
+                    .cmp(&b.status)
+                    .then(a.percent_recovered.total_cmp(&b.percent_recovered))
+            })?;
+        Some(Readiness {
+            elapsed: (now - last_trained).max(TimeDelta::zero()),
+            ..limiting
+        })
+    }
+}
+
+fn hours(hours: u32) -> TimeDelta {
+    TimeDelta::hours(i64::from(hours))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap()
+    }
+
+    fn model() -> RecoveryModel {
+        RecoveryModel::new(&RecoverySettings::default())
+    }
+
+    fn assess_after(muscle: &str, elapsed_hours: i64) -> Readiness {
+        let trained_at = now() - TimeDelta::hours(elapsed_hours);
+        model().assess(muscle, trained_at, &Intensity::default(), now())
+    }
+
+    #[test]
+    fn status_follows_multiples_of_the_window() {
+        assert_eq!(assess_after("chest", 0).status, Status::Recovering);
+        assert_eq!(assess_after("chest", 47).status, Status::Recovering);
+        assert_eq!(assess_after("chest", 48).status, Status::Ready);
+        assert_eq!(assess_after("chest", 95).status, Status::Ready);
+        assert_eq!(assess_after("chest", 96).status, Status::Fresh);
+    }
+
+    #[test]
+    fn legs_use_the_longer_window() {
+        let readiness = assess_after("legs", 48);
+        assert_eq!(readiness.window, TimeDelta::hours(72));
+        assert_eq!(readiness.status, Status::Recovering);
+        assert_eq!(assess_after("legs", 72).status, Status::Ready);
+        assert_eq!(assess_after("legs", 144).status, Status::Fresh);
+    }
+
+    #[test]
+    fn percent_recovered_is_clamped() {
+        assert_eq!(assess_after("chest", 24).percent_recovered, 50.0);
+        assert_eq!(assess_after("chest", 48).percent_recovered, 100.0);
+        assert_eq!(assess_after("chest", 500).percent_recovered, 100.0);
+        // Sessions logged in the future count as just trained
+        assert_eq!(assess_after("chest", -5).percent_recovered, 0.0);
+    }
+
+    #[test]
+    fn least_recovered_session_limits_readiness() {
+        let intensity = Intensity::default();
+        let trainings = [
+            (now() - TimeDelta::hours(100), intensity),
+            (now() - TimeDelta::hours(12), intensity),
+        ];
+        let readiness = model().assess_all("chest", &trainings, now()).unwrap();
+        assert_eq!(readiness.status, Status::Recovering);
+        assert_eq!(readiness.percent_recovered, 25.0);
+        assert_eq!(readiness.elapsed, TimeDelta::hours(12));
+        assert!(model().assess_all("chest", &[], now()).is_none());
+    }
+
+    #[test]
+    fn higher_rpe_lengthens_the_window() {
+        let hard = Intensity {
+            rpe: Some(10),
+            volume: None,
+        };
+        let trained_at = now() - TimeDelta::hours(48);
+        let readiness = model().assess("chest", trained_at, &hard, now());
+        assert_eq!(readiness.status, Status::Recovering);
+        assert!(readiness.window > TimeDelta::hours(48));
+    }
+}
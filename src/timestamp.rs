@@ -0,0 +1,270 @@
+use anyhow::{Result, anyhow};
+use chrono::{
+    DateTime, Days, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeDelta, TimeZone,
+};
+use serde::{Deserialize, Deserializer, de::Error};
+
+/// Point in time with the UTC offset it was recorded in, stored as RFC 3339
+pub type Timestamp = DateTime<FixedOffset>;
+
+const DATE_TIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+];
+const TIME_FORMATS: [&str; 2] = ["%H:%M", "%H:%M:%S"];
+
+/// Parses a point in time given as RFC 3339, a local date and time, or relative to `now`,
+/// such as `3h ago`, `yesterday 18:00` or `07:30`
+pub fn parse_at(input: &str, now: DateTime<Local>) -> Result<Timestamp> {
+    let input = input.trim();
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(input) {
+        return Ok(timestamp);
+    }
+    if let Some(naive) = DATE_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(input, format).ok())
+    {
+        return local(naive);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return start_of_day(date);
+    }
+    let lowercase = input.to_lowercase();
+    if let Some(relative) = lowercase.strip_suffix("ago") {
+        return ago(relative, now);
+    }
+    let (day, time) = match lowercase.split_once(char::is_whitespace) {
+        Some((day, time)) => (day, Some(time.trim())),
+        None => (lowercase.as_str(), None),
+    };
+    let date = match day {
+        "today" => Some(now.date_naive()),
+        "yesterday" => now.date_naive().checked_sub_days(Days::new(1)),
+        _ => None,
+    };
+    match (date, time) {
+        (Some(date), Some(time)) => local(date.and_time(parse_time(time)?)),
+        (Some(date), None) => local(date.and_time(now.time())),
+        (None, _) => {
+            let time = parse_time(input).map_err(|_| {
+                anyhow!(
+                    "Unable to understand the time '{input}'. \
+                     Use RFC 3339, YYYY-MM-DD HH:MM, HH:MM, `yesterday 18:00` or `3h ago`."
+                )
+            })?;
+            local(now.date_naive().and_time(time))
+        }
+    }
+}
+
+/// Point in time a duration such as `3h` or `1d 2h` before `now`
+pub fn ago(input: &str, now: DateTime<Local>) -> Result<Timestamp> {
+    now.checked_sub_signed(parse_duration(input)?)
+        .map(|timestamp| timestamp.fixed_offset())
+        .ok_or_else(|| invalid_duration(input))
+}
+
+fn invalid_duration(input: &str) -> anyhow::Error {
+    anyhow!(
+        "Unable to understand the duration '{}'. Use for example 3h, 90m or 1d 2h.",
+        input.trim()
+    )
+}
+
+/// Parses a duration such as `3h`, `90m`, `1d 2h` or `2 days`
+pub fn parse_duration(input: &str) -> Result<TimeDelta> {
+    let invalid = || invalid_duration(input);
+    let mut total = TimeDelta::zero();
+    let mut rest = input.trim();
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let amount: i64 = rest[..digits].parse().map_err(|_| invalid())?;
+        rest = rest[digits..].trim_start();
+        let unit_length = rest
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(rest.len());
+        let unit = match &rest[..unit_length] {
+            "m" | "min" | "mins" | "minute" | "minutes" => TimeDelta::try_minutes(amount),
+            "h" | "hr" | "hrs" | "hour" | "hours" => TimeDelta::try_hours(amount),
+            "d" | "day" | "days" => TimeDelta::try_days(amount),
+            "w" | "week" | "weeks" => TimeDelta::try_weeks(amount),
+            _ => None,
+        }
+        .ok_or_else(invalid)?;
+        total = total.checked_add(&unit).ok_or_else(invalid)?;
+        rest = rest[unit_length..].trim_start_matches([' ', ',']);
+    }
+    Ok(total)
+}
+
+fn parse_time(input: &str) -> Result<NaiveTime> {
+    TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(input, format).ok())
+        .ok_or_else(|| anyhow!("Unable to understand the time of day '{input}'. Use HH:MM."))
+}
+
+fn local(naive: NaiveDateTime) -> Result<Timestamp> {
+    match Local.from_local_datetime(&naive) {
+        LocalResult::Single(timestamp) => Ok(timestamp.fixed_offset()),
+        LocalResult::Ambiguous(earliest, _) => Ok(earliest.fixed_offset()),
+        LocalResult::None => Err(anyhow!("{naive} does not exist in the local time zone")),
+    }
+}
+
+/// Local midnight at the beginning of `date`
+pub fn start_of_day(date: NaiveDate) -> Result<Timestamp> {
+    local(date.and_time(NaiveTime::MIN))
+}
+
+pub fn now() -> Timestamp {
+    Local::now().fixed_offset()
+}
+
+/// Converts a timestamp from before RFC 3339 storage, which kept no UTC offset,
+/// into the local offset of that moment
+pub fn from_legacy(secs_since_epoch: i64, nanos_since_epoch: u32) -> Option<Timestamp> {
+    let utc = DateTime::from_timestamp(secs_since_epoch, nanos_since_epoch)?;
+    Some(utc.with_timezone(&Local).fixed_offset())
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredTimestamp {
+    Rfc3339(Timestamp),
+    Legacy {
+        secs_since_epoch: i64,
+        nanos_since_epoch: u32,
+    },
+}
+
+impl StoredTimestamp {
+    fn into_timestamp<E: Error>(self) -> Result<Timestamp, E> {
+        match self {
+            StoredTimestamp::Rfc3339(timestamp) => Ok(timestamp),
+            StoredTimestamp::Legacy {
+                secs_since_epoch,
+                nanos_since_epoch,
+            } => from_legacy(secs_since_epoch, nanos_since_epoch)
+                .ok_or_else(|| E::custom("timestamp is out of range")),
+        }
+    }
+}
+
+/// Reads RFC 3339 timestamps as well as the legacy `{secs_since_epoch, nanos_since_epoch}`
+/// objects still found in older journals
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Timestamp, D::Error> {
+    StoredTimestamp::deserialize(deserializer)?.into_timestamp()
+}
+
+pub fn deserialize_option<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Timestamp>, D::Error> {
+    Option::<StoredTimestamp>::deserialize(deserializer)?
+        .map(StoredTimestamp::into_timestamp)
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2026, 10, 18, 15, 30, 0)
+            .single()
+            .expect("unambiguous local time")
+    }
+
+    fn parsed(input: &str) -> Timestamp {
+        parse_at(input, now()).expect("valid time")
+    }
+
+    fn local_time(input: &str) -> String {
+        parsed(input)
+            .naive_local()
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    }
+
+    #[test]
+    fn rfc3339_keeps_its_offset() {
+        let timestamp = parsed("2026-10-01T07:30:00+02:00");
+        assert_eq!(timestamp.to_rfc3339(), "2026-10-01T07:30:00+02:00");
+    }
+
+    #[test]
+    fn local_dates_and_times() {
+        assert_eq!(local_time("2026-10-01 07:30"), "2026-10-01 07:30:00");
+        assert_eq!(local_time("2026-10-01T07:30:15"), "2026-10-01 07:30:15");
+        assert_eq!(local_time("2026-10-01"), "2026-10-01 00:00:00");
+        assert_eq!(local_time("07:30"), "2026-10-18 07:30:00");
+    }
+
+    #[test]
+    fn days_relative_to_now() {
+        assert_eq!(local_time("yesterday 18:00"), "2026-10-17 18:00:00");
+        assert_eq!(local_time("Today 06:15:30"), "2026-10-18 06:15:30");
+        assert_eq!(local_time("yesterday"), "2026-10-17 15:30:00");
+        assert!(parse_at("yesterday 25:00", now()).is_err());
+        assert!(parse_at("tomorrow", now()).is_err());
+    }
+
+    #[test]
+    fn durations_ago() {
+        assert_eq!(
+            parsed("3h ago"),
+            (now() - TimeDelta::hours(3)).fixed_offset()
+        );
+        assert_eq!(
+            parsed("1d 2h ago"),
+            (now() - TimeDelta::hours(26)).fixed_offset()
+        );
+    }
+
+    #[test]
+    fn durations_combine_units() {
+        let duration = |input| parse_duration(input).expect("valid duration");
+        assert_eq!(duration("90m"), TimeDelta::minutes(90));
+        assert_eq!(duration("2 days"), TimeDelta::days(2));
+        assert_eq!(duration("1d 2h"), TimeDelta::hours(26));
+        assert_eq!(
+            duration("1w, 3d 30min"),
+            TimeDelta::minutes(10 * 24 * 60 + 30)
+        );
+        for input in ["", "h", "3", "5x", "-3h", "3h ago"] {
+            assert!(parse_duration(input).is_err(), "parsed {input:?}");
+        }
+    }
+
+    #[test]
+    fn overflowing_durations_are_rejected() {
+        for input in [
+            "99999999999999999999w",
+            "15250284452w 15250284452w",
+            "9223372036854775807m",
+        ] {
+            let error = parse_duration(input).expect_err("overflow").to_string();
+            assert!(
+                error.contains("Unable to understand the duration"),
+                "{error}"
+            );
+        }
+        for input in ["99999999w", "1000000000d"] {
+            let error = ago(input, now()).expect_err("overflow").to_string();
+            assert!(
+                error.contains("Unable to understand the duration"),
+                "{error}"
+            );
+        }
+        assert!(parse_at("1000000000d ago", now()).is_err());
+    }
+}
@@ -0,0 +1,60 @@
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+use tempfile::TempDir;
+
+/// The `wr` binary run against its own configuration directory and data file
+pub struct Wr {
+    home: TempDir,
+}
+
+impl Wr {
+    pub fn new() -> Self {
+        Wr {
+            home: tempfile::tempdir().expect("temporary home directory"),
+        }
+    }
+
+    pub fn data_file(&self) -> PathBuf {
+        self.home.path().join("data").join("sessions.json")
+    }
+
+    pub fn home(&self) -> &Path {
+        self.home.path()
+    }
+
+    pub fn command(&self, args: &[&str]) -> Command {
+        let mut command = self.command_without_data_file(&["--data-file"]);
+        command.arg(self.data_file()).args(args);
+        command
+    }
+
+    /// Runs `wr` at the data file locations of its profiles
+    pub fn command_without_data_file(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_wr"));
+        command
+            .env("HOME", self.home())
+            .env("XDG_CONFIG_HOME", self.home().join("config"))
+            .env_remove("WR_DATA")
+            .env_remove("WR_PROFILE")
+            .env_remove("EDITOR")
+            .args(args);
+        command
+    }
+
+    /// Runs `wr` to completion, failing the test unless it succeeds
+    pub fn run(&self, args: &[&str]) -> String {
+        let output = self.command(args).output().expect("wr should run");
+        assert_success(&output, args);
+        String::from_utf8(output.stdout).expect("output should be utf-8")
+    }
+}
+
+pub fn assert_success(output: &Output, args: &[&str]) {
+    assert!(
+        output.status.success(),
+        "wr {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use common::{Wr, assert_success};
+use std::process::Child;
+
+#[test]
+fn parallel_adds_keep_every_session() {
+    const RUNS: usize = 12;
+    let wr = Wr::new();
+    let children: Vec<(String, Child)> = (0..RUNS)
+        .map(|run| {
+            let description = format!("session {run}");
+            let child = wr
+                .command(&["add", &description, "--muscles", "chest"])
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::piped())
+                .spawn()
+                .expect("wr should start");
+            (description, child)
+        })
+        .collect();
+    for (description, child) in children {
+        let output = child.wait_with_output().expect("wr should finish");
+        assert_success(&output, &["add", &description]);
+    }
+    let listed = wr.run(&["list", "--all", "--format", "jsonl"]);
+    assert_eq!(listed.lines().count(), RUNS);
+    for run in 0..RUNS {
+        let description = format!("\"session {run}\"");
+        assert!(listed.contains(&description), "{description} is missing");
+    }
+}
@@ -0,0 +1,99 @@
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use common::Wr;
+use std::{fs, os::unix::fs::PermissionsExt};
+
+fn edit_with(wr: &Wr, identifier: &str, editor: &str) -> std::process::Output {
+    wr.command(&["edit", identifier])
+        .env("VISUAL", editor)
+        .output()
+        .expect("wr should run")
+}
+
+fn only_identifier(wr: &Wr) -> String {
+    let listed = wr.run(&["list", "--format", "tsv"]);
+    let row = listed.lines().nth(1).expect("one session");
+    row.split('\t')
+        .next()
+        .expect("identifier column")
+        .to_owned()
+}
+
+#[test]
+fn editing_a_session_into_the_future_is_rejected() {
+    let wr = Wr::new();
+    wr.run(&[
+        "add",
+        "legs",
+        "--muscles",
+        "quads",
+        "--at",
+        "2026-01-02 10:00",
+    ]);
+    let identifier = only_identifier(&wr);
+    let before = fs::read(wr.data_file()).expect("data file");
+    let output = edit_with(&wr, &identifier, "sed -i s/2026-01-02T/2999-01-02T/");
+    assert!(!output.status.success());
+    let error = String::from_utf8_lossy(&output.stderr);
+    assert!(error.contains("is in the future"), "{error}");
+    assert_eq!(fs::read(wr.data_file()).expect("data file"), before);
+}
+
+#[test]
+fn editing_uses_a_private_temporary_file() {
+    let wr = Wr::new();
+    wr.run(&[
+        "add",
+        "legs",
+        "--muscles",
+        "quads",
+        "--at",
+        "2026-01-02 10:00",
+    ]);
+    let identifier = only_identifier(&wr);
+    let seen = wr.home().join("seen");
+    // Records the path and permissions of the file the editor is given
+    let script = wr.home().join("editor.sh");
+    fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\nls -ln \"$1\" > '{}'\nsed -i s/legs/squats/ \"$1\"\n",
+            seen.display()
+        ),
+    )
+    .expect("editor script");
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).expect("executable script");
+    let output = edit_with(&wr, &identifier, script.to_str().expect("utf-8 path"));
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let seen = fs::read_to_string(seen).expect("editor ran");
+    let mode = seen.split_whitespace().next().expect("file mode");
+    let path = seen.split_whitespace().last().expect("file path");
+    assert_eq!(mode, "-rw-------");
+    assert!(path.contains(&format!("wr-{identifier}-")), "{path}");
+    assert!(!fs::exists(path).expect("checkable path"));
+    assert!(wr.run(&["show", &identifier]).contains("squats"));
+}
+
+#[test]
+fn blank_tags_are_rejected() {
+    let wr = Wr::new();
+    wr.run(&["add", "legs", "--at", "2026-01-02 10:00"]);
+    let identifier = only_identifier(&wr);
+    let before = fs::read(wr.data_file()).expect("data file");
+    for action in ["add", "remove"] {
+        let output = wr
+            .command(&["tag", action, &identifier, "   "])
+            .output()
+            .expect("wr should run");
+        assert!(!output.status.success());
+        let error = String::from_utf8_lossy(&output.stderr);
+        assert!(error.contains("Tag must not be empty"), "{error}");
+    }
+    assert_eq!(fs::read(wr.data_file()).expect("data file"), before);
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::{Wr, assert_success};
+
+fn run_with_data_env(wr: &Wr, args: &[&str]) -> String {
+    let output = wr
+        .command_without_data_file(args)
+        .env("WR_DATA", wr.data_file())
+        .output()
+        .expect("wr should run");
+    assert_success(&output, args);
+    String::from_utf8(output.stdout).expect("output should be utf-8")
+}
+
+#[test]
+fn data_environment_variable_only_moves_the_default_profile() {
+    let wr = Wr::new();
+    run_with_data_env(&wr, &["profile", "create", "alice"]);
+    run_with_data_env(&wr, &["add", "shared", "--muscles", "chest"]);
+    run_with_data_env(
+        &wr,
+        &["--profile", "alice", "add", "own", "--muscles", "quads"],
+    );
+    let alice_path = run_with_data_env(&wr, &["--profile", "alice", "config", "path"]);
+    assert!(alice_path.contains("alice"), "{alice_path}");
+    let default = run_with_data_env(&wr, &["list", "--format", "jsonl"]);
+    let alice = run_with_data_env(&wr, &["--profile", "alice", "list", "--format", "jsonl"]);
+    assert!(
+        default.contains("\"shared\"") && !default.contains("\"own\""),
+        "{default}"
+    );
+    assert!(
+        alice.contains("\"own\"") && !alice.contains("\"shared\""),
+        "{alice}"
+    );
+}
+
+#[test]
+fn profile_names_cannot_leave_the_profiles_directory() {
+    let wr = Wr::new();
+    wr.run(&["profile", "create", "alice"]);
+    for args in [
+        &["--profile", "..", "list"][..],
+        &["profile", "delete", ".."],
+        &["profile", "default", "alice/.."],
+    ] {
+        let output = wr.command(args).output().expect("wr should run");
+        assert!(!output.status.success(), "wr {args:?} succeeded");
+        let error = String::from_utf8_lossy(&output.stderr);
+        assert!(error.contains("Profile names may only contain"), "{error}");
+    }
+    assert!(wr.run(&["profile", "list"]).contains("alice"));
+}
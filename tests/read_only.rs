@@ -0,0 +1,48 @@
+mod common;
+
+use common::Wr;
+use std::{fs, thread, time::Duration};
+
+#[test]
+fn read_only_commands_leave_the_data_file_alone() {
+    let wr = Wr::new();
+    wr.run(&["add", "push day", "-e", "bench 3x5@100kg"]);
+    wr.run(&["add", "legs", "--muscles", "quads,glutes", "--rpe", "8"]);
+    let listed = wr.run(&["list", "--format", "jsonl"]);
+    let identifier = listed
+        .split("\"identifier\":\"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .expect("listed session should have an identifier")
+        .to_owned();
+    let data_file = wr.data_file();
+    let contents = fs::read(&data_file).expect("data file");
+    let modified = fs::metadata(&data_file)
+        .and_then(|metadata| metadata.modified())
+        .expect("modification time");
+    // Leaves any rewrite room to show up in the modification time
+    thread::sleep(Duration::from_millis(20));
+    for args in [
+        &["list"][..],
+        &["list", "--all", "--format", "json"],
+        &["show", &identifier],
+        &["stats"],
+        &["stats", "--format", "csv"],
+        &["recovery"],
+        &["load"],
+        &["pr"],
+        &["calendar"],
+        &["tags"],
+    ] {
+        wr.run(args);
+        assert_eq!(
+            fs::read(&data_file).expect("data file"),
+            contents,
+            "wr {args:?} changed the data file"
+        );
+        let now_modified = fs::metadata(&data_file)
+            .and_then(|metadata| metadata.modified())
+            .expect("modification time");
+        assert_eq!(now_modified, modified, "wr {args:?} rewrote the data file");
+    }
+}